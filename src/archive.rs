@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::tree::FsNode;
+
+/// Archive formats whose entries can be listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// `.zip`
+    Zip,
+    /// Uncompressed `.tar`
+    Tar,
+    /// `.tar.gz` or `.tgz`
+    TarGz,
+    /// `.tar.zst` or `.tzst`
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// Guesses the format from a file name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+struct ArchiveEntry {
+    path: String,
+    compressed: u64,
+    uncompressed: u64,
+    is_dir: bool,
+}
+
+/// Reads the entry list of an archive and turns it into virtual child nodes.
+///
+/// Every returned node has `uncompressed` set. Their `size` is the share of
+/// the archive file they account for, so the children of an archive add up
+/// to at most the archive's length on disk. Zip records a compressed size
+/// per entry, which leaves out the headers and central directory; for
+/// tarballs the archive length is split in proportion to the uncompressed
+/// sizes, rounding each share down. The archive node itself keeps its real
+/// length either way.
+pub fn read_archive(path: &Path, format: ArchiveFormat, archive_len: u64) -> io::Result<Vec<Arc<FsNode>>> {
+    let mut entries = match format {
+        ArchiveFormat::Zip => read_zip(path)?,
+        ArchiveFormat::Tar => {
+            // A plain tar can seek past each file's data instead of reading it
+            let mut archive = tar::Archive::new(BufReader::new(File::open(path)?));
+            tar_entries(archive.entries_with_seek()?)?
+        }
+        ArchiveFormat::TarGz => read_tar(flate2::read::GzDecoder::new(BufReader::new(File::open(path)?)))?,
+        ArchiveFormat::TarZst => read_tar(zstd::stream::read::Decoder::new(File::open(path)?)?)?,
+    };
+
+    if format != ArchiveFormat::Zip {
+        let total: u64 = entries.iter().map(|e| e.uncompressed).sum();
+        if total > 0 {
+            for entry in &mut entries {
+                entry.compressed = (entry.uncompressed as u128 * archive_len as u128 / total as u128) as u64;
+            }
+        }
+    }
+
+    let mut root = Vec::new();
+    for entry in entries {
+        insert_entry(&mut root, &entry);
+    }
+    Ok(root)
+}
+
+fn read_zip(path: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(path)?))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut entries = Vec::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        // by_index_raw only reads headers, nothing gets decompressed
+        let file = zip.by_index_raw(i).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        entries.push(ArchiveEntry {
+            path: file.name().to_string(),
+            compressed: file.compressed_size(),
+            uncompressed: file.size(),
+            is_dir: file.is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+// Compressed tarballs can only be read from start to end
+fn read_tar<R: Read>(reader: R) -> io::Result<Vec<ArchiveEntry>> {
+    let mut archive = tar::Archive::new(reader);
+    tar_entries(archive.entries()?)
+}
+
+fn tar_entries<R: Read>(tar_entries: tar::Entries<'_, R>) -> io::Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    for entry in tar_entries {
+        let entry = entry?;
+        let header = entry.header();
+        entries.push(ArchiveEntry {
+            path: entry.path()?.to_string_lossy().to_string(),
+            compressed: 0,
+            uncompressed: header.size()?,
+            is_dir: header.entry_type().is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+fn insert_entry(siblings: &mut Vec<Arc<FsNode>>, entry: &ArchiveEntry) {
+    let components: Vec<&str> = entry
+        .path
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect();
+    let Some((last, dirs)) = components.split_last() else {
+        return;
+    };
+
+    let mut level = siblings;
+    for dir in dirs {
+        let index = match level.iter().position(|n| n.is_dir && n.name == *dir) {
+            Some(i) => i,
+            None => {
+                level.push(Arc::new(virtual_dir(dir)));
+                level.len() - 1
+            }
+        };
+        // Nothing else holds these nodes yet, so make_mut never clones
+        let node = Arc::make_mut(&mut level[index]);
+        node.size += entry.compressed;
+        node.uncompressed = Some(node.uncompressed.unwrap_or(0) + entry.uncompressed);
+        level = &mut node.children;
+    }
+
+    if entry.is_dir {
+        if !level.iter().any(|n| n.is_dir && n.name == *last) {
+            level.push(Arc::new(virtual_dir(last)));
+        }
+    } else {
+        level.push(Arc::new(FsNode {
+            name: last.to_string(),
+            size: entry.compressed,
+            uncompressed: Some(entry.uncompressed),
+            ..Default::default()
+        }));
+    }
+}
+
+fn virtual_dir(name: &str) -> FsNode {
+    FsNode {
+        name: name.to_string(),
+        is_dir: true,
+        uncompressed: Some(0),
+        ..Default::default()
+    }
+}
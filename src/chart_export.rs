@@ -0,0 +1,84 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use resvg::{tiny_skia, usvg};
+
+use crate::svg_chart;
+use crate::FolderInfo;
+
+/// Which chart to draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    /// Horizontal bars, largest first.
+    Bar,
+    /// A pie of the rows.
+    Pie,
+    /// The pie with further rings for the contents of each slice.
+    Sunburst {
+        /// Number of rings, counting the pie itself.
+        rings: usize,
+    },
+}
+
+/// Renders a chart to `path` at the given pixel size. The format follows
+/// the file extension: `.svg` is written as is, anything else becomes PNG.
+pub fn export_chart(
+    path: &Path,
+    kind: ChartKind,
+    rows: &[FolderInfo],
+    limit: usize,
+    width: u32,
+    height: u32,
+) -> io::Result<()> {
+    let svg = match kind {
+        ChartKind::Bar => svg_chart::bar_chart_svg(rows, limit, width as f64, height as f64),
+        ChartKind::Pie => svg_chart::pie_chart_svg(rows, limit, width as f64, height as f64),
+        ChartKind::Sunburst { rings } => svg_chart::sunburst_svg(rows, limit, rings, width as f64, height as f64),
+    };
+
+    let is_svg = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("svg"))
+        .unwrap_or(false);
+    if is_svg {
+        fs::write(path, svg)
+    } else {
+        fs::write(path, render_png(&svg, width, height)?)
+    }
+}
+
+// Tried in order for the generic sans-serif family, which fontdb maps to
+// Arial by default even on systems that don't have it
+const SANS_SERIF_FAMILIES: [&str; 7] = [
+    "Segoe UI",
+    "Helvetica",
+    "Arial",
+    "DejaVu Sans",
+    "Liberation Sans",
+    "Noto Sans",
+    "Ubuntu",
+];
+
+fn render_png(svg: &str, width: u32, height: u32) -> io::Result<Vec<u8>> {
+    let mut options = usvg::Options::default();
+    let fontdb = options.fontdb_mut();
+    fontdb.load_system_fonts();
+    let installed = SANS_SERIF_FAMILIES.iter().find(|family| {
+        fontdb
+            .faces()
+            .any(|face| face.families.iter().any(|(name, _)| name == *family))
+    });
+    if let Some(family) = installed {
+        fontdb.set_sans_serif_family(*family);
+    }
+    let tree = usvg::Tree::from_str(svg, &options)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "image size must not be zero"))?;
+    let scale_x = width as f32 / tree.size().width();
+    let scale_y = height as f32 / tree.size().height();
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale_x, scale_y), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(io::Error::other)
+}
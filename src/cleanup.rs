@@ -0,0 +1,125 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::tree::FsNode;
+use crate::FolderInfo;
+
+/// An empty directory or a zero-byte file.
+#[derive(Debug, Clone)]
+pub struct EmptyItem {
+    /// Full path of the item.
+    pub path: PathBuf,
+    /// A directory rather than a file.
+    pub is_dir: bool,
+}
+
+/// How many empty items one scanned top-level folder holds.
+#[derive(Debug, Clone)]
+pub struct FolderEmptyCounts {
+    /// The top-level folder.
+    pub folder: PathBuf,
+    /// Outermost empty directories, counted as listed in the report.
+    pub empty_dirs: usize,
+    /// Files with no content.
+    pub zero_byte_files: usize,
+}
+
+/// Everything [`find_empty`] found.
+#[derive(Debug, Clone, Default)]
+pub struct EmptyReport {
+    /// Empty items in the order they were found.
+    pub items: Vec<EmptyItem>,
+    /// Counts for the folders that have any empty items.
+    pub per_folder: Vec<FolderEmptyCounts>,
+}
+
+/// Collects recursively empty directories and zero-byte files.
+///
+/// A directory counts as empty when nothing but other empty directories
+/// lives below it. Only the outermost such directory is listed, since
+/// removing it takes its empty subdirectories with it.
+pub fn find_empty(results: &[FolderInfo]) -> EmptyReport {
+    let mut report = EmptyReport::default();
+    for info in results {
+        let parent = info.path.parent().unwrap_or(Path::new(""));
+        collect_empty(&info.node, parent, &mut report.items);
+        report.per_folder.push(FolderEmptyCounts {
+            folder: info.path.clone(),
+            empty_dirs: 0,
+            zero_byte_files: 0,
+        });
+    }
+    report.recount();
+    report
+}
+
+impl EmptyReport {
+    /// Refreshes `per_folder` after items were added or removed. Folders
+    /// without any empty items are dropped.
+    pub fn recount(&mut self) {
+        for counts in &mut self.per_folder {
+            counts.empty_dirs = 0;
+            counts.zero_byte_files = 0;
+            for item in self.items.iter().filter(|item| item.path.starts_with(&counts.folder)) {
+                if item.is_dir {
+                    counts.empty_dirs += 1;
+                } else {
+                    counts.zero_byte_files += 1;
+                }
+            }
+        }
+        self.per_folder.retain(|counts| counts.empty_dirs + counts.zero_byte_files > 0);
+    }
+}
+
+fn collect_empty(node: &FsNode, parent: &Path, items: &mut Vec<EmptyItem>) {
+    let path = parent.join(&node.name);
+    if !node.is_dir {
+        if node.size == 0 {
+            items.push(EmptyItem { path, is_dir: false });
+        }
+    } else if !contains_files(node) {
+        items.push(EmptyItem { path, is_dir: true });
+    } else {
+        for child in &node.children {
+            collect_empty(child, &path, items);
+        }
+    }
+}
+
+fn contains_files(node: &FsNode) -> bool {
+    node.children
+        .iter()
+        .any(|child| !child.is_dir || contains_files(child))
+}
+
+/// Deletes an item found by [`find_empty`], re-checking on disk that it is
+/// still empty so nothing created since the scan is lost.
+pub fn remove_empty(item: &EmptyItem) -> io::Result<()> {
+    if item.is_dir {
+        remove_empty_dir(&item.path)
+    } else {
+        if fs::metadata(&item.path)?.len() != 0 {
+            return Err(io::Error::other("file is no longer empty"));
+        }
+        fs::remove_file(&item.path)
+    }
+}
+
+fn remove_empty_dir(path: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            remove_empty_dir(&entry.path())?;
+        } else {
+            return Err(io::Error::other(format!(
+                "{} is no longer empty",
+                path.display()
+            )));
+        }
+    }
+    // remove_dir refuses non-empty directories, so this is safe even if a
+    // file appeared after the check above
+    fs::remove_dir(path)
+}
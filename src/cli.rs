@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use folder_size_analyzer::filter::{self, Filter};
+use folder_size_analyzer::search::{self, Matcher};
+use folder_size_analyzer::{export, format_size, ncdu, FolderInfo, ScanEvent, ScanOptions, ScanReport, Scanner};
+
+/// Everything went fine.
+pub const EXIT_OK: i32 = 0;
+/// The scan finished, but some folders could not be read and are missing.
+pub const EXIT_PARTIAL: i32 = 1;
+/// Bad command line.
+pub const EXIT_USAGE: i32 = 2;
+/// The scan could not run at all, or the output could not be written.
+pub const EXIT_FAILURE: i32 = 3;
+
+const USAGE: &str = "\
+Usage: folder-size-analyzer scan <path> [options]
+       folder-size-analyzer tui [path] [--archives]
+
+Scans the folders directly below <path> and prints them by size.
+`tui` browses the scan in the terminal instead (see `tui --help`).
+Run without arguments to open the window, in builds that have one.
+
+Options:
+  --top <n>          Number of folders to print, 0 for all (default 10)
+  --format <format>  table, json, csv, ncdu or folded (default table)
+  --filter <expr>    Print the files and folders at any depth that match
+                     the expression instead, largest first, e.g.
+                     'size > 1G and ext in (log, gz) and mtime < 90d'
+                     (table or csv only)
+  --archives         Look inside zip and tar archives
+  -h, --help         Show this help
+
+Exit status:
+  0  success
+  1  some folders could not be read and are missing from the output
+  2  invalid arguments
+  3  the scan or writing the output failed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+    Ncdu,
+    Folded,
+}
+
+struct ScanArgs {
+    path: PathBuf,
+    top: usize,
+    format: OutputFormat,
+    options: ScanOptions,
+    filter: Option<Filter>,
+}
+
+/// Whether the arguments ask for the command line instead of the window.
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    matches!(
+        args.first().map(String::as_str),
+        Some("scan" | "tui" | "help" | "-h" | "--help" | "-V" | "--version")
+    )
+}
+
+/// Runs a command line invocation and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("-V" | "--version") => {
+            println!("folder-size-analyzer {}", env!("CARGO_PKG_VERSION"));
+            EXIT_OK
+        }
+        Some("tui") => crate::tui::run(&args[1..]),
+        Some("scan") => match parse_scan_args(&args[1..]) {
+            Ok(Some(scan_args)) => run_scan(scan_args),
+            Ok(None) => {
+                print_help();
+                EXIT_OK
+            }
+            Err(message) => {
+                eprintln!("error: {}\n\n{}", message, USAGE);
+                EXIT_USAGE
+            }
+        },
+        _ => {
+            print_help();
+            EXIT_OK
+        }
+    }
+}
+
+fn print_help() {
+    println!("{}\n\nFilter expressions:\n{}", USAGE, filter::SYNTAX);
+}
+
+// Ok(None) means help was requested
+fn parse_scan_args(args: &[String]) -> Result<Option<ScanArgs>, String> {
+    let mut path = None;
+    let mut top = 10;
+    let mut format = OutputFormat::Table;
+    let mut options = ScanOptions::default();
+    let mut filter = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--top" => {
+                let value = iter.next().ok_or("--top needs a number")?;
+                top = value
+                    .parse()
+                    .map_err(|_| format!("--top expects a number, got '{}'", value))?;
+            }
+            "--format" => {
+                let value = iter.next().ok_or("--format needs a value")?;
+                format = match value.as_str() {
+                    "table" => OutputFormat::Table,
+                    "json" => OutputFormat::Json,
+                    "csv" => OutputFormat::Csv,
+                    "ncdu" => OutputFormat::Ncdu,
+                    "folded" => OutputFormat::Folded,
+                    other => return Err(format!("unknown format '{}'", other)),
+                };
+            }
+            "--filter" => {
+                let value = iter.next().ok_or("--filter needs an expression")?;
+                filter = Some(Filter::parse(value).map_err(|e| format!("bad filter: {}", e))?);
+            }
+            "--archives" => options.look_inside_archives = true,
+            other if other.starts_with('-') => return Err(format!("unknown option '{}'", other)),
+            other => {
+                if path.replace(PathBuf::from(other)).is_some() {
+                    return Err("only one path can be scanned at a time".to_string());
+                }
+            }
+        }
+    }
+
+    let path = path.ok_or("missing the path to scan")?;
+    if filter.is_some() && !matches!(format, OutputFormat::Table | OutputFormat::Csv) {
+        return Err("--filter only works with the table and csv formats".to_string());
+    }
+    Ok(Some(ScanArgs {
+        path,
+        top,
+        format,
+        options,
+        filter,
+    }))
+}
+
+fn run_scan(args: ScanArgs) -> i32 {
+    if !args.path.is_dir() {
+        eprintln!("error: not a directory: {}", args.path.display());
+        return EXIT_FAILURE;
+    }
+
+    let Some(ScanReport { folders: mut results, info }) = scan(&args) else {
+        return EXIT_FAILURE;
+    };
+    // Percentages are shares of the whole scan, not just of what is printed
+    let scan_total: u64 = results.iter().map(|info| info.size).sum();
+    let limit = if args.top > 0 { args.top } else { usize::MAX };
+    if let Some(filter) = args.filter.clone() {
+        results = search::search(&results, &Matcher::Expression(filter), limit).hits;
+    } else {
+        results.truncate(limit);
+    }
+
+    let stdout = io::stdout();
+    let written = match args.format {
+        OutputFormat::Table => write_table(stdout.lock(), &results, scan_total),
+        OutputFormat::Json => export::write_json(stdout.lock(), &info, &results),
+        OutputFormat::Csv => export::write_csv(stdout.lock(), &results, scan_total),
+        OutputFormat::Ncdu => ncdu::write_export(stdout.lock(), &info.root, &results),
+        OutputFormat::Folded => export::write_folded(stdout.lock(), &results),
+    };
+    if let Err(e) = written {
+        // A closed pipe (e.g. `| head`) is not worth an error message
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("error: could not write output: {}", e);
+        }
+        return EXIT_FAILURE;
+    }
+
+    if info.errors.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_PARTIAL
+    }
+}
+
+// Runs the scan on a worker thread, reporting unreadable folders to stderr
+// as soon as they are found
+fn scan(args: &ScanArgs) -> Option<ScanReport> {
+    let (sender, events) = mpsc::channel();
+    let scanner = Scanner::new(&args.path).options(args.options.clone());
+    std::thread::spawn(move || scanner.scan_with_events(sender));
+
+    for event in events {
+        match event {
+            ScanEvent::Error(error) => {
+                eprintln!("warning: skipped {}: {}", error.path.display(), error.message);
+            }
+            ScanEvent::Finished(report) => return Some(report),
+            ScanEvent::Failed(error) => {
+                eprintln!("error: could not read {}: {}", error.path.display(), error.message);
+                return None;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Percentages are shares of `scan_total`
+fn write_table(writer: impl Write, results: &[FolderInfo], scan_total: u64) -> io::Result<()> {
+    let mut out = io::BufWriter::new(writer);
+    writeln!(out, "{:>12}  {:>6}  {:>9}  Folder", "Size", "%", "Files")?;
+    for info in results {
+        let percent = if scan_total > 0 {
+            info.size as f64 / scan_total as f64 * 100.0
+        } else {
+            0.0
+        };
+        writeln!(
+            out,
+            "{:>12}  {:>5.1}%  {:>9}  {}",
+            format_size(info.size),
+            percent,
+            info.node.file_count(),
+            info.path.display()
+        )?;
+    }
+    let total = listed_total(results);
+    writeln!(out, "{:>12}  {:>6}  {:>9}  (total of listed folders)", format_size(total), "", "")?;
+    out.flush()
+}
+
+// Size of everything listed. Filtered output can list a folder along with
+// entries inside it, which are already part of its size.
+fn listed_total(results: &[FolderInfo]) -> u64 {
+    let listed: HashSet<&Path> = results.iter().map(|info| info.path.as_path()).collect();
+    results
+        .iter()
+        .filter(|info| !info.path.ancestors().skip(1).any(|ancestor| listed.contains(ancestor)))
+        .map(|info| info.size)
+        .sum()
+}
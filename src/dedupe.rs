@@ -0,0 +1,318 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::duplicates::{self, DuplicateSet};
+
+const COMPARE_BUFFER_BYTES: usize = 256 * 1024;
+// Temporary names tried next to a duplicate before giving up
+const TEMP_NAME_ATTEMPTS: usize = 100;
+
+/// How a duplicate is made to share its original's data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkMode {
+    /// Another name for the original file.
+    HardLink,
+    /// A copy-on-write clone, on filesystems that support them.
+    Reflink,
+}
+
+impl LinkMode {
+    /// Name to show in a choice of modes.
+    pub fn label(self) -> &'static str {
+        match self {
+            LinkMode::HardLink => "Hard link",
+            LinkMode::Reflink => "Reflink clone",
+        }
+    }
+
+    /// What happens to a replaced copy's permissions, owner and
+    /// modification time.
+    pub fn metadata_note(self) -> &'static str {
+        match self {
+            LinkMode::HardLink => {
+                "A hard link is another name for the kept file, so each replaced copy takes on the kept \
+                 file's permissions, owner and modification time."
+            }
+            LinkMode::Reflink => {
+                "Each replaced copy keeps its permissions and modification time, but is owned by you \
+                 afterwards."
+            }
+        }
+    }
+}
+
+/// One duplicate that will be replaced by a link to `original`.
+#[derive(Debug, Clone)]
+pub struct Replacement {
+    /// The copy that is kept.
+    pub original: PathBuf,
+    /// The copy that becomes a link.
+    pub duplicate: PathBuf,
+    /// Length of each copy.
+    pub size: u64,
+}
+
+/// What [`apply`] did.
+#[derive(Debug, Clone, Default)]
+pub struct DedupeOutcome {
+    /// Duplicates that were replaced with a link.
+    pub replaced: usize,
+    /// Duplicates that were already hard links to their original. They
+    /// are left as they are and reclaim nothing.
+    pub already_linked: usize,
+    /// Bytes freed by the replaced duplicates.
+    pub reclaimed: u64,
+    /// Every duplicate that now shares its original's data, whether it was
+    /// replaced or already linked.
+    pub linked: Vec<PathBuf>,
+    /// Duplicates left as they were, with the reason.
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+/// Builds the dry-run list for the given sets. The first path of every set
+/// is kept and all other copies are replaced.
+pub fn plan<'a>(sets: impl IntoIterator<Item = &'a DuplicateSet>) -> Vec<Replacement> {
+    let mut plan = Vec::new();
+    for set in sets {
+        let Some((original, copies)) = set.paths.split_first() else {
+            continue;
+        };
+        for duplicate in copies {
+            plan.push(Replacement {
+                original: original.clone(),
+                duplicate: duplicate.clone(),
+                size: set.size,
+            });
+        }
+    }
+    plan
+}
+
+/// Replaces every planned duplicate with a link to its original. Each pair
+/// is compared byte-for-byte first, so files that changed since the
+/// duplicate scan are left alone and reported as failures.
+pub fn apply(plan: &[Replacement], mode: LinkMode) -> DedupeOutcome {
+    let mut outcome = DedupeOutcome::default();
+    for replacement in plan {
+        let original_id = duplicates::file_id(&replacement.original);
+        if original_id.is_some() && original_id == duplicates::file_id(&replacement.duplicate) {
+            outcome.already_linked += 1;
+            outcome.linked.push(replacement.duplicate.clone());
+            continue;
+        }
+        match replace_with_link(replacement, mode) {
+            Ok(()) => {
+                outcome.replaced += 1;
+                outcome.reclaimed += replacement.size;
+                outcome.linked.push(replacement.duplicate.clone());
+            }
+            Err(e) => outcome.failures.push((replacement.duplicate.clone(), e.to_string())),
+        }
+    }
+    outcome
+}
+
+fn replace_with_link(replacement: &Replacement, mode: LinkMode) -> io::Result<()> {
+    let Replacement { original, duplicate, .. } = replacement;
+    if !same_content(original, duplicate)? {
+        return Err(io::Error::other("content differs from the original"));
+    }
+
+    // Link to a temporary name next to the duplicate and rename it over the
+    // top, so the duplicate is never missing if linking fails halfway.
+    let temp = link_to_temp(original, duplicate, mode)?;
+    let renamed = keep_metadata(duplicate, &temp, mode).and_then(|()| fs::rename(&temp, duplicate));
+    if let Err(e) = renamed {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+// Links `original` to an unused name next to `duplicate`. A name left
+// behind by an earlier, interrupted run is skipped rather than reused.
+fn link_to_temp(original: &Path, duplicate: &Path, mode: LinkMode) -> io::Result<PathBuf> {
+    let file_name = duplicate
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?
+        .to_string_lossy();
+    for attempt in 0..TEMP_NAME_ATTEMPTS {
+        let temp = duplicate.with_file_name(format!(".{}.{}.fsa-link", file_name, attempt));
+        let linked = match mode {
+            LinkMode::HardLink => fs::hard_link(original, &temp),
+            LinkMode::Reflink => reflink_copy::reflink(original, &temp),
+        };
+        match linked {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            linked => return linked.map(|()| temp),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "no free temporary name next to the file"))
+}
+
+// A reflink clone is a new file, so it is given the duplicate's permissions
+// and modification time. A hard link shares everything with the original.
+fn keep_metadata(duplicate: &Path, temp: &Path, mode: LinkMode) -> io::Result<()> {
+    if mode == LinkMode::HardLink {
+        return Ok(());
+    }
+    let metadata = fs::metadata(duplicate)?;
+    // The time first, while the clone is still writable
+    File::options().write(true).open(temp)?.set_modified(metadata.modified()?)?;
+    fs::set_permissions(temp, metadata.permissions())
+}
+
+fn same_content(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut file_a = File::open(a)?;
+    let mut file_b = File::open(b)?;
+    if file_a.metadata()?.len() != file_b.metadata()?.len() {
+        return Ok(false);
+    }
+
+    let mut buf_a = vec![0u8; COMPARE_BUFFER_BYTES];
+    let mut buf_b = vec![0u8; COMPARE_BUFFER_BYTES];
+    loop {
+        let n = read_full(&mut file_a, &mut buf_a)?;
+        let m = read_full(&mut file_b, &mut buf_b)?;
+        if n != m || buf_a[..n] != buf_b[..m] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+// Fills as much of `buf` as the file allows; only returns short at EOF.
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = file.read(&mut buf[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes `original` and `duplicate` into `dir` and plans replacing the
+    // duplicate with a link to the original
+    fn pair(dir: &Path, original: &[u8], duplicate: &[u8]) -> Vec<Replacement> {
+        let set = DuplicateSet {
+            size: original.len() as u64,
+            paths: vec![dir.join("original"), dir.join("duplicate")],
+        };
+        fs::write(&set.paths[0], original).unwrap();
+        fs::write(&set.paths[1], duplicate).unwrap();
+        plan([&set])
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn plan_keeps_the_first_path() {
+        let set = DuplicateSet {
+            size: 4,
+            paths: vec!["a".into(), "b".into(), "c".into()],
+        };
+        let plan = plan([&set]);
+        assert_eq!(plan.len(), 2);
+        assert!(plan.iter().all(|r| r.original == Path::new("a") && r.size == 4));
+        assert_eq!(plan[1].duplicate, Path::new("c"));
+    }
+
+    #[test]
+    fn replaces_a_duplicate_with_a_hard_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = pair(dir.path(), b"same data", b"same data");
+        let outcome = apply(&plan, LinkMode::HardLink);
+        assert_eq!((outcome.replaced, outcome.reclaimed), (1, 9));
+        assert!(outcome.failures.is_empty());
+        assert_eq!(outcome.linked, [dir.path().join("duplicate")]);
+        assert_eq!(
+            duplicates::file_id(&dir.path().join("original")),
+            duplicates::file_id(&dir.path().join("duplicate"))
+        );
+        assert_eq!(file_names(dir.path()), ["duplicate", "original"]);
+    }
+
+    #[test]
+    fn leaves_a_changed_duplicate_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = pair(dir.path(), b"same data", b"different");
+        let outcome = apply(&plan, LinkMode::HardLink);
+        assert_eq!((outcome.replaced, outcome.reclaimed), (0, 0));
+        assert!(outcome.linked.is_empty());
+        assert_eq!(outcome.failures.len(), 1);
+        assert_eq!(outcome.failures[0].1, "content differs from the original");
+        assert_eq!(fs::read(dir.path().join("duplicate")).unwrap(), b"different");
+        assert_eq!(file_names(dir.path()), ["duplicate", "original"]);
+    }
+
+    #[test]
+    fn skips_a_temporary_name_left_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = pair(dir.path(), b"same data", b"same data");
+        let stale = dir.path().join(".duplicate.0.fsa-link");
+        fs::write(&stale, b"stale").unwrap();
+        let outcome = apply(&plan, LinkMode::HardLink);
+        assert_eq!(outcome.replaced, 1);
+        assert_eq!(fs::read(&stale).unwrap(), b"stale");
+        assert_eq!(
+            duplicates::file_id(&dir.path().join("original")),
+            duplicates::file_id(&dir.path().join("duplicate"))
+        );
+        assert_eq!(file_names(dir.path()), [".duplicate.0.fsa-link", "duplicate", "original"]);
+    }
+
+    #[test]
+    fn counts_copies_that_are_already_linked() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = pair(dir.path(), b"same data", b"");
+        fs::remove_file(dir.path().join("duplicate")).unwrap();
+        fs::hard_link(dir.path().join("original"), dir.path().join("duplicate")).unwrap();
+        let outcome = apply(&plan, LinkMode::HardLink);
+        assert_eq!((outcome.replaced, outcome.already_linked, outcome.reclaimed), (0, 1, 0));
+        assert_eq!(outcome.linked, [dir.path().join("duplicate")]);
+        assert!(outcome.failures.is_empty());
+    }
+
+    #[test]
+    fn reflinks_keep_the_duplicates_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = pair(dir.path(), b"same data", b"same data");
+        let duplicate = dir.path().join("duplicate");
+        let modified = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        File::options().write(true).open(&duplicate).unwrap().set_modified(modified).unwrap();
+        let mut permissions = fs::metadata(&duplicate).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&duplicate, permissions).unwrap();
+
+        let outcome = apply(&plan, LinkMode::Reflink);
+        let metadata = fs::metadata(&duplicate).unwrap();
+        if outcome.failures.is_empty() {
+            assert_eq!(outcome.replaced, 1);
+        } else {
+            // Filesystems without clones fail to link, leaving no temporary
+            // file and the duplicate as it was
+            assert_eq!(outcome.replaced, 0);
+            assert_eq!(fs::read(&duplicate).unwrap(), b"same data");
+        }
+        assert_eq!(metadata.modified().unwrap(), modified);
+        assert!(metadata.permissions().readonly());
+        assert_eq!(file_names(dir.path()), ["duplicate", "original"]);
+    }
+}
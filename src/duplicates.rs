@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::FolderInfo;
+
+// How many bytes from the start of a file go into the partial hash
+const PARTIAL_HASH_BYTES: usize = 16 * 1024;
+const READ_BUFFER_BYTES: usize = 256 * 1024;
+
+// Files of one size that may still share content
+type SizeGroup = (u64, Vec<PathBuf>);
+
+/// A group of files with identical content.
+#[derive(Debug, Clone)]
+pub struct DuplicateSet {
+    /// Length of each file.
+    pub size: u64,
+    /// The files, at least two. The first is the one a dedupe keeps.
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateSet {
+    /// Bytes that would be freed by keeping a single copy.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// Everything [`find_duplicates`] found.
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateReport {
+    /// Sets sorted by wasted bytes, largest first.
+    pub sets: Vec<DuplicateSet>,
+    /// Wasted bytes attributed to each scanned top-level folder, largest first.
+    /// The first path of a set counts as the original, every other copy is
+    /// charged to the folder it lives in.
+    pub wasted_per_folder: Vec<(PathBuf, u64)>,
+    /// Files looked at, before any were ruled out by size.
+    pub files_compared: usize,
+    /// Files that could not be read for hashing.
+    pub unreadable: usize,
+    /// Extra names of files that were already found under another name.
+    /// Hard links share their data, so they are left out of the sets.
+    pub hard_links: usize,
+}
+
+impl DuplicateReport {
+    /// Bytes that keeping one copy of every set would free.
+    pub fn total_wasted(&self) -> u64 {
+        self.sets.iter().map(DuplicateSet::wasted_bytes).sum()
+    }
+
+    /// Drops copies that no longer waste space, such as the ones a dedupe
+    /// just turned into links, along with sets that have one file left.
+    pub fn remove_copies(&mut self, copies: &[PathBuf], results: &[FolderInfo]) {
+        let copies: HashSet<&PathBuf> = copies.iter().collect();
+        for set in &mut self.sets {
+            // The kept first path stays even if it is listed
+            let mut index = 0;
+            set.paths.retain(|path| {
+                index += 1;
+                index == 1 || !copies.contains(path)
+            });
+        }
+        self.sets.retain(|set| set.paths.len() > 1);
+        self.wasted_per_folder = wasted_per_folder(&self.sets, results);
+    }
+}
+
+/// Finds files with identical content among the scanned folders.
+///
+/// Candidates are narrowed in three passes: equal size, equal hash of the
+/// first few KiB, then equal hash of the whole file. Hard links to the same
+/// file are collapsed into one path before hashing. Hashing runs on the
+/// rayon pool.
+pub fn find_duplicates(results: &[FolderInfo]) -> DuplicateReport {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut files_compared = 0;
+    for info in results {
+        let parent = info.path.parent().unwrap_or(Path::new(""));
+        info.node.visit_files(parent, &mut |path, node| {
+            files_compared += 1;
+            // Empty files are all "identical" and free anyway
+            if node.size > 0 {
+                by_size.entry(node.size).or_default().push(path);
+            }
+        });
+    }
+
+    let candidates: Vec<SizeGroup> = by_size
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+    let (candidates, hard_links) = collapse_hard_links(candidates);
+
+    let (partial_groups, mut unreadable) = group_by_hash(candidates, |path, _| partial_hash(path));
+    let (full_groups, full_unreadable) = group_by_hash(partial_groups, |path, size| {
+        // Files no bigger than the partial window are already fully hashed
+        if size <= PARTIAL_HASH_BYTES as u64 {
+            partial_hash(path)
+        } else {
+            full_hash(path)
+        }
+    });
+    unreadable += full_unreadable;
+
+    let mut sets: Vec<DuplicateSet> = full_groups
+        .into_iter()
+        .map(|(size, mut paths)| {
+            paths.sort();
+            DuplicateSet { size, paths }
+        })
+        .collect();
+    sets.sort_by(|a, b| b.wasted_bytes().cmp(&a.wasted_bytes()).then_with(|| a.paths.cmp(&b.paths)));
+
+    let wasted_per_folder = wasted_per_folder(&sets, results);
+    DuplicateReport {
+        sets,
+        wasted_per_folder,
+        files_compared,
+        unreadable,
+        hard_links,
+    }
+}
+
+fn wasted_per_folder(sets: &[DuplicateSet], results: &[FolderInfo]) -> Vec<(PathBuf, u64)> {
+    let mut per_folder: HashMap<PathBuf, u64> = HashMap::new();
+    for set in sets {
+        for path in set.paths.iter().skip(1) {
+            if let Some(folder) = results.iter().find(|info| path.starts_with(&info.path)) {
+                *per_folder.entry(folder.path.clone()).or_default() += set.size;
+            }
+        }
+    }
+    let mut wasted: Vec<(PathBuf, u64)> = per_folder.into_iter().collect();
+    wasted.sort_by_key(|(_, wasted)| std::cmp::Reverse(*wasted));
+    wasted
+}
+
+// Keeps one path per file in every group, so that hard links are neither
+// hashed twice nor reported as copies. Returns the groups that still have
+// more than one file and the number of paths dropped.
+fn collapse_hard_links(groups: Vec<SizeGroup>) -> (Vec<SizeGroup>, usize) {
+    let collapsed: Vec<(SizeGroup, usize)> = groups
+        .into_par_iter()
+        .map(|(size, mut paths)| {
+            // Sorted first so the same name is kept on every run
+            paths.sort();
+            let before = paths.len();
+            let mut seen = HashSet::new();
+            paths.retain(|path| match file_id(path) {
+                Some(id) => seen.insert(id),
+                None => true,
+            });
+            let dropped = before - paths.len();
+            ((size, paths), dropped)
+        })
+        .collect();
+
+    let dropped = collapsed.iter().map(|(_, dropped)| dropped).sum();
+    let groups = collapsed
+        .into_iter()
+        .map(|(group, _)| group)
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+    (groups, dropped)
+}
+
+/// Identifies the file behind `path` as (device, inode), so that two paths
+/// with the same id are hard links to one file. `None` when it can't be
+/// read or the platform has no such id.
+#[cfg(unix)]
+pub(crate) fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(windows)]
+pub(crate) fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::fileapi::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION};
+
+    // The volume serial and file index play the part of device and inode
+    let file = File::open(path).ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return None;
+    }
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    Some((info.dwVolumeSerialNumber as u64, index))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn file_id(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+// Splits every group further by the given hash, dropping groups that end up
+// with a single member. Returns the new groups and the number of files that
+// could not be read.
+fn group_by_hash<F>(groups: Vec<SizeGroup>, hash: F) -> (Vec<SizeGroup>, usize)
+where
+    F: Fn(&Path, u64) -> io::Result<u128> + Sync,
+{
+    let hashed: Vec<_> = groups
+        .into_par_iter()
+        .map(|(size, paths)| {
+            let hashes: Vec<(PathBuf, io::Result<u128>)> = paths
+                .into_par_iter()
+                .map(|path| {
+                    let h = hash(&path, size);
+                    (path, h)
+                })
+                .collect();
+            (size, hashes)
+        })
+        .collect();
+
+    let mut unreadable = 0;
+    let mut out = Vec::new();
+    for (size, entries) in hashed {
+        let mut by_hash: HashMap<u128, Vec<PathBuf>> = HashMap::new();
+        for (path, h) in entries {
+            match h {
+                Ok(h) => by_hash.entry(h).or_default().push(path),
+                Err(_) => unreadable += 1,
+            }
+        }
+        out.extend(
+            by_hash
+                .into_values()
+                .filter(|paths| paths.len() > 1)
+                .map(|paths| (size, paths)),
+        );
+    }
+    (out, unreadable)
+}
+
+fn partial_hash(path: &Path) -> io::Result<u128> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; PARTIAL_HASH_BYTES];
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = file.read(&mut buf[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(xxhash_rust::xxh3::xxh3_128(&buf[..filled]))
+}
+
+fn full_hash(path: &Path) -> io::Result<u128> {
+    let mut file = File::open(path)?;
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    let mut buf = vec![0u8; READ_BUFFER_BYTES];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.digest128())
+}
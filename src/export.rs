@@ -0,0 +1,316 @@
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::tree::{FsNode, ScanError, ScanInfo, ScanOptions};
+use crate::FolderInfo;
+
+const JSON_FORMAT: &str = "folder-size-analyzer";
+/// Bumped whenever the layout of [`JsonExport`] changes incompatibly.
+pub const JSON_VERSION: u32 = 1;
+
+/// Top level of a JSON export.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonExport {
+    /// Always `folder-size-analyzer`, to recognise the file.
+    pub format: String,
+    /// The [`JSON_VERSION`] the file was written with.
+    pub version: u32,
+    /// The directory whose folders were scanned.
+    pub root: PathBuf,
+    /// When the scan started, if known.
+    pub started: Option<DateTime<Utc>>,
+    /// When the scan finished, if known.
+    pub finished: Option<DateTime<Utc>>,
+    /// The options the scan ran with.
+    pub options: ScanOptions,
+    /// Folders that could not be read.
+    pub errors: Vec<ScanError>,
+    /// The scanned top-level folders with their full subtrees.
+    pub folders: Vec<JsonNode>,
+}
+
+/// One [`FsNode`] in a JSON export; the fields mean the same as there.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonNode {
+    /// See [`FsNode::name`].
+    pub name: String,
+    /// See [`FsNode::size`].
+    pub size: u64,
+    /// See [`FsNode::allocated`].
+    pub allocated: u64,
+    /// See [`FsNode::is_dir`].
+    pub is_dir: bool,
+    /// See [`FsNode::modified`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+    /// See [`FsNode::uncompressed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uncompressed: Option<u64>,
+    /// See [`FsNode::children`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<JsonNode>,
+}
+
+impl From<&FsNode> for JsonNode {
+    fn from(node: &FsNode) -> Self {
+        JsonNode {
+            name: node.name.clone(),
+            size: node.size,
+            allocated: node.allocated,
+            is_dir: node.is_dir,
+            modified: node.modified.map(DateTime::from),
+            uncompressed: node.uncompressed,
+            children: node.children.iter().map(|child| JsonNode::from(child.as_ref())).collect(),
+        }
+    }
+}
+
+impl From<JsonNode> for FsNode {
+    fn from(node: JsonNode) -> Self {
+        FsNode {
+            name: node.name,
+            size: node.size,
+            allocated: node.allocated,
+            is_dir: node.is_dir,
+            modified: node.modified.map(SystemTime::from),
+            uncompressed: node.uncompressed,
+            children: node.children.into_iter().map(|child| Arc::new(FsNode::from(child))).collect(),
+        }
+    }
+}
+
+/// Writes the whole scan, tree and metadata, as pretty-printed JSON.
+pub fn write_json(writer: impl Write, info: &ScanInfo, results: &[FolderInfo]) -> io::Result<()> {
+    let export = JsonExport {
+        format: JSON_FORMAT.to_string(),
+        version: JSON_VERSION,
+        root: info.root.clone(),
+        started: info.started.map(DateTime::from),
+        finished: info.finished.map(DateTime::from),
+        options: info.options.clone(),
+        errors: info.errors.clone(),
+        folders: results.iter().map(|folder| JsonNode::from(folder.node.as_ref())).collect(),
+    };
+    let mut out = io::BufWriter::new(writer);
+    serde_json::to_writer_pretty(&mut out, &export)?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+/// Reads a file written by [`write_json`] back into scan results.
+pub fn read_json(reader: impl Read) -> io::Result<(ScanInfo, Vec<FolderInfo>)> {
+    let export: JsonExport = from_nested_json(reader)?;
+    if export.format != JSON_FORMAT {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a folder-size-analyzer export"));
+    }
+    if export.version > JSON_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("export version {} is newer than this build supports ({})", export.version, JSON_VERSION),
+        ));
+    }
+
+    let mut folders: Vec<FolderInfo> = export
+        .folders
+        .into_iter()
+        .map(|node| {
+            let node = FsNode::from(node);
+            FolderInfo {
+                path: export.root.join(&node.name),
+                size: node.size,
+                node: Arc::new(node),
+            }
+        })
+        .collect();
+    folders.sort_by_key(|info| std::cmp::Reverse(info.size));
+
+    let info = ScanInfo {
+        root: export.root,
+        started: export.started.map(SystemTime::from),
+        finished: export.finished.map(SystemTime::from),
+        options: export.options,
+        errors: export.errors,
+    };
+    Ok((info, folders))
+}
+
+// JSON nested deeper than this is refused on import. Everything that walks
+// a tree recurses, from converting and dropping it to drawing it, so a
+// corrupt or hostile file must not get that far. An export uses two levels
+// per folder, an ncdu dump one.
+const MAX_JSON_NESTING: usize = 1024;
+
+// Like `serde_json::from_reader`, but allowing nesting deeper than its limit
+// of 128 levels, which deep folder trees easily exceed, up to
+// `MAX_JSON_NESTING`. The parser's stack grows on the heap as needed.
+pub(crate) fn from_nested_json<T: DeserializeOwned>(mut reader: impl Read) -> io::Result<T> {
+    let mut text = Vec::new();
+    reader.read_to_end(&mut text)?;
+    check_nesting(&text)?;
+
+    let mut deserializer = serde_json::Deserializer::from_slice(&text);
+    deserializer.disable_recursion_limit();
+    let value = T::deserialize(serde_stacker::Deserializer::new(&mut deserializer))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+// Counts brackets outside of strings, before anything deep gets built
+fn check_nesting(text: &[u8]) -> io::Result<()> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in text {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > MAX_JSON_NESTING {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("JSON is nested deeper than {} levels", MAX_JSON_NESTING),
+                    ));
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Writes one CSV row per entry of the given view. Percentages are shares
+/// of `total`, which the caller picks to match what the rows are part of.
+pub fn write_csv(writer: impl Write, rows: &[FolderInfo], total: u64) -> io::Result<()> {
+    let mut out = io::BufWriter::new(writer);
+
+    writeln!(out, "path,size_bytes,percent,file_count,modified_utc")?;
+    for info in rows {
+        let percent = if total > 0 {
+            info.size as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        };
+        writeln!(
+            out,
+            "{},{},{:.2},{},{}",
+            csv_field(&info.path.display().to_string()),
+            info.size,
+            percent,
+            info.node.file_count(),
+            info.node.modified.map(format_timestamp).unwrap_or_default()
+        )?;
+    }
+    out.flush()
+}
+
+/// Writes the tree as folded stacks, one `folder;sub;file bytes` line per
+/// file, for flamegraph.pl, inferno or speedscope. Stacks start at the
+/// scanned folders, and entries inside opened archives get their own
+/// frames below the archive.
+pub fn write_folded(writer: impl Write, results: &[FolderInfo]) -> io::Result<()> {
+    let mut out = io::BufWriter::new(writer);
+    let mut stack = String::new();
+    for info in results {
+        write_folded_node(&mut out, &mut stack, &info.node)?;
+    }
+    out.flush()
+}
+
+fn write_folded_node(out: &mut impl Write, stack: &mut String, node: &FsNode) -> io::Result<()> {
+    let parent_len = stack.len();
+    if !stack.is_empty() {
+        stack.push(';');
+    }
+    // Semicolons separate frames and the line ends the record
+    stack.extend(node.name.chars().map(|c| if matches!(c, ';' | '\n' | '\r') { '_' } else { c }));
+
+    if node.children.is_empty() {
+        // Zero-sized entries would not show up in a flame graph anyway
+        if node.size > 0 {
+            writeln!(out, "{} {}", stack, node.size)?;
+        }
+    } else {
+        for child in &node.children {
+            write_folded_node(out, stack, child)?;
+        }
+    }
+    stack.truncate(parent_len);
+    Ok(())
+}
+
+/// Formats a timestamp as `YYYY-MM-DD HH:MM:SS` in UTC, which spreadsheets
+/// recognise as a date.
+pub fn format_timestamp(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An export whose single folder has `depth` levels of subfolders
+    fn nested_export(depth: usize) -> String {
+        let mut tree = String::from(r#"{"name":"leaf","size":1,"allocated":1,"is_dir":false}"#);
+        for level in 0..depth {
+            tree = format!(r#"{{"name":"d{}","size":1,"allocated":1,"is_dir":true,"children":[{}]}}"#, level, tree);
+        }
+        format!(
+            r#"{{"format":"{}","version":1,"root":"/r","started":null,"finished":null,"options":{{}},"errors":[],"folders":[{}]}}"#,
+            JSON_FORMAT, tree
+        )
+    }
+
+    #[test]
+    fn reads_trees_deeper_than_the_default_limit() {
+        let (_, folders) = read_json(nested_export(500).as_bytes()).unwrap();
+        let mut node = folders[0].node.as_ref();
+        let mut depth = 0;
+        while let Some(child) = node.children.first() {
+            node = child;
+            depth += 1;
+        }
+        assert_eq!(depth, 500);
+        assert_eq!(node.name, "leaf");
+    }
+
+    #[test]
+    fn refuses_trees_nested_too_deep() {
+        let error = read_json(nested_export(5000).as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "JSON is nested deeper than 1024 levels");
+    }
+
+    #[test]
+    fn brackets_in_names_do_not_count() {
+        let brackets = "[{".repeat(2000);
+        let text = nested_export(1).replace("leaf", &brackets);
+        let (_, folders) = read_json(text.as_bytes()).unwrap();
+        assert_eq!(folders[0].node.children[0].name, brackets);
+    }
+}
@@ -0,0 +1,644 @@
+use std::time::{Duration, SystemTime};
+
+use crate::FolderInfo;
+
+/// Summary of the expression syntax, for help texts.
+pub const SYNTAX: &str = "\
+Tests:        size, files, mtime, name, ext, path, type
+Comparisons:  <  <=  >  >=  =  !=, `~` for a glob, `in (a, b)` for a list
+Sizes:        1500, 10K, 2.5M, 1G, 1T (powers of 1000), 4KiB, 1GiB
+mtime:        an age such as 30m, 12h, 90d, 2w, 6mo, 1y, or a date
+              (2024-01-31, UTC); `mtime < 90d` means changed in the
+              last 90 days, `mtime < 2024-01-31` before that day and
+              `mtime = 2024-01-31` at any time on it
+type:         file or dir
+Combine with `and`, `or`, `not` and parentheses. Names compare
+ignoring case; quote values with spaces or commas.";
+
+/// A parsed filter expression such as
+/// `size > 1G and ext in (log, gz) and mtime < 90d`. See [`SYNTAX`].
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+    // Ages are measured from when the filter was parsed
+    now: SystemTime,
+}
+
+impl Filter {
+    /// Parses `text`, or describes the first problem and where it is.
+    pub fn parse(text: &str) -> Result<Filter, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: text.len(),
+        };
+        let expr = parser.or()?;
+        if let Some((at, _)) = parser.tokens.get(parser.pos) {
+            return Err(format!("unexpected input at column {}", at + 1));
+        }
+        Ok(Filter {
+            expr,
+            now: SystemTime::now(),
+        })
+    }
+
+    /// Whether the entry passes the filter.
+    pub fn matches(&self, entry: &FolderInfo) -> bool {
+        self.expr.eval(entry, self.now)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Test(Test),
+}
+
+#[derive(Debug, Clone)]
+enum Test {
+    Size(Cmp, u64),
+    Files(Cmp, u64),
+    Age(Cmp, Duration),
+    // A UTC day, from its first moment up to the next day's
+    Modified(Cmp, SystemTime, SystemTime),
+    Name(TextTest),
+    Ext(TextTest),
+    Path(TextTest),
+    IsDir(Cmp, bool),
+}
+
+#[derive(Debug, Clone)]
+enum TextTest {
+    Eq(String),
+    Ne(String),
+    In(Vec<String>),
+    Glob(globset::GlobMatcher),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Cmp {
+    fn holds<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            Cmp::Lt => left < right,
+            Cmp::Le => left <= right,
+            Cmp::Gt => left > right,
+            Cmp::Ge => left >= right,
+            Cmp::Eq => left == right,
+            Cmp::Ne => left != right,
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, entry: &FolderInfo, now: SystemTime) -> bool {
+        match self {
+            Expr::And(left, right) => left.eval(entry, now) && right.eval(entry, now),
+            Expr::Or(left, right) => left.eval(entry, now) || right.eval(entry, now),
+            Expr::Not(inner) => !inner.eval(entry, now),
+            Expr::Test(test) => test.eval(entry, now),
+        }
+    }
+}
+
+impl Test {
+    fn eval(&self, entry: &FolderInfo, now: SystemTime) -> bool {
+        let node = &entry.node;
+        match self {
+            Test::Size(cmp, size) => cmp.holds(node.size, *size),
+            Test::Files(cmp, files) => cmp.holds(node.file_count(), *files),
+            // Times in the future, from clock skew, count as just changed
+            Test::Age(cmp, age) => node
+                .modified
+                .map(|modified| now.duration_since(modified).unwrap_or_default())
+                .is_some_and(|entry_age| cmp.holds(entry_age, *age)),
+            Test::Modified(cmp, start, end) => node.modified.is_some_and(|modified| {
+                let on_day = *start <= modified && modified < *end;
+                match cmp {
+                    Cmp::Lt => modified < *start,
+                    Cmp::Le => modified < *end,
+                    Cmp::Gt => modified >= *end,
+                    Cmp::Ge => modified >= *start,
+                    Cmp::Eq => on_day,
+                    Cmp::Ne => !on_day,
+                }
+            }),
+            Test::Name(test) => test.eval(&node.name),
+            // Folders have no extension, so no extension test matches them
+            Test::Ext(test) => !node.is_dir && extension(&node.name).is_some_and(|ext| test.eval(&ext)),
+            Test::Path(test) => test.eval(&entry.path.to_string_lossy()),
+            Test::IsDir(cmp, is_dir) => cmp.holds(node.is_dir, *is_dir),
+        }
+    }
+}
+
+impl TextTest {
+    fn eval(&self, value: &str) -> bool {
+        let value = value.to_lowercase();
+        match self {
+            TextTest::Eq(text) => value == *text,
+            TextTest::Ne(text) => value != *text,
+            TextTest::In(texts) => texts.contains(&value),
+            TextTest::Glob(glob) => glob.is_match(&value),
+        }
+    }
+}
+
+fn extension(name: &str) -> Option<String> {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => Some(ext.to_lowercase()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    // Quoted, so never a keyword
+    Text(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+// Tokens with the byte offset they start at
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(at, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push((
+                    at,
+                    match c {
+                        '(' => Token::LParen,
+                        ')' => Token::RParen,
+                        _ => Token::Comma,
+                    },
+                ));
+            }
+            '<' | '>' | '=' | '!' | '~' => {
+                chars.next();
+                let followed_by_equals = chars.next_if(|&(_, next)| next == '=').is_some();
+                let op = match (c, followed_by_equals) {
+                    ('<', false) => "<",
+                    ('<', true) => "<=",
+                    ('>', false) => ">",
+                    ('>', true) => ">=",
+                    ('=', _) => "=",
+                    ('!', true) => "!=",
+                    ('~', false) => "~",
+                    _ => return Err(format!("unknown operator at column {}", at + 1)),
+                };
+                tokens.push((at, Token::Op(op)));
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => value.push(escaped),
+                            None => return Err(format!("unterminated string at column {}", at + 1)),
+                        },
+                        Some((_, end)) if end == c => break,
+                        Some((_, other)) => value.push(other),
+                        None => return Err(format!("unterminated string at column {}", at + 1)),
+                    }
+                }
+                tokens.push((at, Token::Text(value)));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| !c.is_whitespace() && !"()<>=!~,\"'".contains(c)) {
+                    word.push(c);
+                }
+                tokens.push((at, Token::Word(word)));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    // Length of the text, for errors at its end
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(at, _)| *at) + 1
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self.peek().cloned().ok_or("unexpected end of expression")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), String> {
+        if self.peek() == Some(&expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected {} at column {}", what, self.column()))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while self.eat_keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let inner = self.or()?;
+            self.expect(Token::RParen, "')'")?;
+            return Ok(inner);
+        }
+        self.test().map(Expr::Test)
+    }
+
+    fn test(&mut self) -> Result<Test, String> {
+        let column = self.column();
+        let field = match self.next()? {
+            Token::Word(word) => word.to_lowercase(),
+            _ => return Err(format!("expected a test such as `size > 1G` at column {}", column)),
+        };
+        if !matches!(field.as_str(), "size" | "files" | "mtime" | "name" | "ext" | "path" | "type") {
+            return Err(format!(
+                "unknown test '{}' at column {} (expected size, files, mtime, name, ext, path or type)",
+                field, column
+            ));
+        }
+
+        let op_column = self.column();
+        if self.eat_keyword("in") {
+            let values = self.list()?;
+            return match field.as_str() {
+                "name" | "path" => Ok(text_test(&field, TextTest::In(lowercase(values)))),
+                "ext" => Ok(Test::Ext(TextTest::In(lowercase(values).iter().map(|v| strip_dot(v)).collect()))),
+                _ => Err(format!("`in` needs a name, ext or path test (column {})", op_column)),
+            };
+        }
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            _ => return Err(format!("expected a comparison at column {}", op_column)),
+        };
+        let value_column = self.column();
+        let value = self.value()?;
+        let invalid = |what: &str| format!("invalid {} '{}' at column {}", what, value, value_column);
+
+        if matches!(field.as_str(), "name" | "ext" | "path") {
+            let value = value.to_lowercase();
+            let value = if field == "ext" { strip_dot(&value) } else { value };
+            let test = match op {
+                "=" => TextTest::Eq(value),
+                "!=" => TextTest::Ne(value),
+                "~" => TextTest::Glob(
+                    globset::GlobBuilder::new(&value)
+                        .build()
+                        .map_err(|e| format!("{} at column {}", e.kind(), value_column))?
+                        .compile_matcher(),
+                ),
+                _ => return Err(format!("{} can only be compared with =, !=, ~ or in (column {})", field, op_column)),
+            };
+            return Ok(text_test(&field, test));
+        }
+
+        let cmp = match op {
+            "<" => Cmp::Lt,
+            "<=" => Cmp::Le,
+            ">" => Cmp::Gt,
+            ">=" => Cmp::Ge,
+            "=" => Cmp::Eq,
+            "!=" => Cmp::Ne,
+            _ => return Err(format!("{} can't be matched with ~ (column {})", field, op_column)),
+        };
+        match field.as_str() {
+            "size" => parse_size(&value).map(|size| Test::Size(cmp, size)).ok_or_else(|| invalid("size")),
+            "files" => value.parse().map(|files| Test::Files(cmp, files)).map_err(|_| invalid("file count")),
+            "mtime" => {
+                if let Ok(date) = chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                    let start = SystemTime::from(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+                    Ok(Test::Modified(cmp, start, start + Duration::from_secs(86400)))
+                } else {
+                    parse_age(&value).map(|age| Test::Age(cmp, age)).ok_or_else(|| invalid("age or date"))
+                }
+            }
+            _ => {
+                let is_dir = match value.to_lowercase().as_str() {
+                    "dir" | "d" | "folder" => true,
+                    "file" | "f" => false,
+                    _ => return Err(invalid("type (expected file or dir)")),
+                };
+                match cmp {
+                    Cmp::Eq | Cmp::Ne => Ok(Test::IsDir(cmp, is_dir)),
+                    _ => Err(format!("type can only be compared with = or != (column {})", op_column)),
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<String, String> {
+        let column = self.column();
+        match self.next()? {
+            Token::Word(word) | Token::Text(word) => Ok(word),
+            _ => Err(format!("expected a value at column {}", column)),
+        }
+    }
+
+    fn list(&mut self) -> Result<Vec<String>, String> {
+        self.expect(Token::LParen, "'(' after `in`")?;
+        let mut values = vec![self.value()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            values.push(self.value()?);
+        }
+        self.expect(Token::RParen, "',' or ')'")?;
+        Ok(values)
+    }
+}
+
+fn text_test(field: &str, test: TextTest) -> Test {
+    match field {
+        "name" => Test::Name(test),
+        "ext" => Test::Ext(test),
+        _ => Test::Path(test),
+    }
+}
+
+fn lowercase(values: Vec<String>) -> Vec<String> {
+    values.into_iter().map(|value| value.to_lowercase()).collect()
+}
+
+// `ext = .log` and `ext = log` mean the same
+fn strip_dot(ext: &str) -> String {
+    ext.strip_prefix('.').unwrap_or(ext).to_string()
+}
+
+// A byte count with an optional unit, decimal like `format_size`
+fn parse_size(text: &str) -> Option<u64> {
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "ki" | "kib" => 1024.0,
+        "mi" | "mib" => 1024.0 * 1024.0,
+        "gi" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        "ti" | "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+fn parse_age(text: &str) -> Option<Duration> {
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let seconds = match unit.to_lowercase().as_str() {
+        "s" => 1.0,
+        "m" | "min" => 60.0,
+        "h" => 3600.0,
+        "d" => 86400.0,
+        "w" => 7.0 * 86400.0,
+        "mo" => 30.0 * 86400.0,
+        "y" => 365.0 * 86400.0,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(number * seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::FsNode;
+
+    // 2024-01-31 12:00:00 UTC
+    const NOW: u64 = 1_706_702_400;
+
+    fn file(name: &str, size: u64, modified: u64) -> FolderInfo {
+        let node = FsNode {
+            name: name.to_string(),
+            size,
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(modified)),
+            ..Default::default()
+        };
+        FolderInfo {
+            path: PathBuf::from("/data").join(name),
+            size,
+            node: Arc::new(node),
+        }
+    }
+
+    fn dir(name: &str, children: Vec<FolderInfo>) -> FolderInfo {
+        let node = FsNode {
+            name: name.to_string(),
+            size: children.iter().map(|child| child.size).sum(),
+            is_dir: true,
+            children: children.into_iter().map(|child| child.node).collect(),
+            ..Default::default()
+        };
+        FolderInfo {
+            path: PathBuf::from("/data").join(name),
+            size: node.size,
+            node: Arc::new(node),
+        }
+    }
+
+    fn matches(expr: &str, entry: &FolderInfo) -> bool {
+        let mut filter = Filter::parse(expr).unwrap_or_else(|e| panic!("{}: {}", expr, e));
+        filter.now = SystemTime::UNIX_EPOCH + Duration::from_secs(NOW);
+        filter.matches(entry)
+    }
+
+    fn error(expr: &str) -> String {
+        Filter::parse(expr).expect_err(expr)
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let log = file("app.log", 10, NOW);
+        // a or (b and c): true even though c fails
+        assert!(matches("ext = log or size > 1K and name = nope", &log));
+        // (a or b) and c
+        assert!(!matches("(ext = log or size > 1K) and name = nope", &log));
+        assert!(matches("name = nope or ext = log", &log));
+    }
+
+    #[test]
+    fn not_negates_the_next_test_or_group() {
+        let log = file("app.log", 10, NOW);
+        assert!(matches("not ext = gz", &log));
+        assert!(!matches("not ext = log", &log));
+        assert!(matches("not (ext = log and size > 1K)", &log));
+        assert!(!matches("not not ext = gz", &log));
+        assert!(matches("not ext = gz and size < 1K", &log));
+    }
+
+    #[test]
+    fn in_lists_ignore_case_and_leading_dots() {
+        let log = file("App.LOG", 10, NOW);
+        assert!(matches("ext in (gz, .log)", &log));
+        assert!(matches("name in (\"app.log\", other)", &log));
+        assert!(!matches("ext in (gz, zst)", &log));
+        assert_eq!(error("size in (1, 2)"), "`in` needs a name, ext or path test (column 6)");
+    }
+
+    #[test]
+    fn quoted_values_and_escapes() {
+        let spaced = file("my file, final.txt", 10, NOW);
+        assert!(matches("name = \"my file, final.txt\"", &spaced));
+        assert!(matches("name = 'MY FILE, FINAL.TXT'", &spaced));
+
+        let quoted = file("say \"hi\".txt", 10, NOW);
+        assert!(matches(r#"name = "say \"hi\".txt""#, &quoted));
+        assert!(matches(r#"name = 'say "hi".txt'"#, &quoted));
+
+        // A quoted keyword is just a value
+        let named_and = file("and", 10, NOW);
+        assert!(matches("name = \"and\"", &named_and));
+    }
+
+    #[test]
+    fn size_units() {
+        assert_eq!(parse_size("1500"), Some(1500));
+        assert_eq!(parse_size("10K"), Some(10_000));
+        assert_eq!(parse_size("2.5M"), Some(2_500_000));
+        assert_eq!(parse_size("1gb"), Some(1_000_000_000));
+        assert_eq!(parse_size("4KiB"), Some(4096));
+        assert_eq!(parse_size("1GiB"), Some(1 << 30));
+        assert_eq!(parse_size("1T"), Some(1_000_000_000_000));
+        assert_eq!(parse_size("3X"), None);
+        assert_eq!(parse_size("K"), None);
+
+        let big = file("big.iso", 2_000_000_000, NOW);
+        assert!(matches("size > 1G", &big));
+        assert!(!matches("size > 2GiB", &big));
+    }
+
+    #[test]
+    fn age_units() {
+        assert_eq!(parse_age("30m"), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_age("12h"), Some(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_age("90d"), Some(Duration::from_secs(90 * 86400)));
+        assert_eq!(parse_age("2w"), Some(Duration::from_secs(14 * 86400)));
+        assert_eq!(parse_age("6mo"), Some(Duration::from_secs(180 * 86400)));
+        assert_eq!(parse_age("1.5y"), Some(Duration::from_secs(547 * 86400 + 43200)));
+        assert_eq!(parse_age("5"), None);
+
+        let week_old = file("old.txt", 10, NOW - 7 * 86400);
+        assert!(matches("mtime > 6d", &week_old));
+        assert!(matches("mtime < 2w", &week_old));
+        assert!(!matches("mtime < 12h", &week_old));
+    }
+
+    #[test]
+    fn future_times_count_as_age_zero() {
+        let future = file("skewed.txt", 10, NOW + 3600);
+        assert!(matches("mtime < 1h", &future));
+        assert!(!matches("mtime > 1h", &future));
+    }
+
+    #[test]
+    fn dates_cover_the_whole_utc_day() {
+        // 2024-01-30 00:00:00 UTC
+        let day_start = NOW - 86400 - 43200;
+        let morning = file("morning.txt", 10, day_start);
+        let evening = file("evening.txt", 10, day_start + 86399);
+        let next_day = file("next.txt", 10, day_start + 86400);
+
+        assert!(matches("mtime = 2024-01-30", &morning));
+        assert!(matches("mtime = 2024-01-30", &evening));
+        assert!(!matches("mtime = 2024-01-30", &next_day));
+        assert!(matches("mtime != 2024-01-30", &next_day));
+        assert!(!matches("mtime != 2024-01-30", &evening));
+
+        assert!(matches("mtime < 2024-01-31", &evening));
+        assert!(!matches("mtime < 2024-01-30", &morning));
+        assert!(matches("mtime <= 2024-01-30", &evening));
+        assert!(matches("mtime > 2024-01-29", &morning));
+        assert!(!matches("mtime > 2024-01-30", &evening));
+        assert!(matches("mtime >= 2024-01-30", &morning));
+    }
+
+    #[test]
+    fn type_and_file_count() {
+        let folder = dir("logs", vec![file("a.log", 10, NOW), file("b.log", 20, NOW)]);
+        assert!(matches("type = dir and files = 2", &folder));
+        assert!(matches("type != file", &folder));
+        // Folders have no extension
+        assert!(!matches("ext = logs", &dir("x.logs", vec![])));
+    }
+
+    #[test]
+    fn errors_name_the_column() {
+        assert_eq!(error("size >> 1G"), "expected a value at column 7");
+        assert_eq!(
+            error("sise > 1G"),
+            "unknown test 'sise' at column 1 (expected size, files, mtime, name, ext, path or type)"
+        );
+        assert_eq!(error("size > 1X"), "invalid size '1X' at column 8");
+        assert_eq!(error("name = a and"), "unexpected end of expression");
+        assert_eq!(error("(size > 1G"), "expected ')' at column 11");
+        assert_eq!(error("size > 1G size"), "unexpected input at column 11");
+        assert_eq!(error("name = \"abc"), "unterminated string at column 8");
+        assert_eq!(error("size ! 1G"), "unknown operator at column 6");
+        assert_eq!(error("name < x"), "name can only be compared with =, !=, ~ or in (column 6)");
+        assert_eq!(error("type > dir"), "type can only be compared with = or != (column 6)");
+        assert_eq!(error("ext in (gz"), "expected ',' or ')' at column 11");
+    }
+}
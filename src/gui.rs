@@ -0,0 +1,2264 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::cmp::Reverse;
+use std::fs;
+
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+
+use folder_size_analyzer::chart_export::{self, ChartKind};
+use folder_size_analyzer::cleanup::{self, EmptyReport};
+use folder_size_analyzer::dedupe::{self, DedupeOutcome, LinkMode, Replacement};
+use folder_size_analyzer::duplicates::{self, DuplicateReport};
+use folder_size_analyzer::search::{self, Matcher, SearchMode, SearchResults};
+use folder_size_analyzer::sparse::{self, AllocationEntry};
+use folder_size_analyzer::{export, filter, format_size, html_report, listing, ncdu, owner, svg_chart};
+use folder_size_analyzer::{FolderInfo, FsNode, ScanError, ScanEvent, ScanInfo, ScanOptions, ScanReport, Scanner};
+
+use crate::treemap::{self, TreemapColor};
+use crate::{icicle, sunburst};
+
+// Search matches listed at most, largest first
+const SEARCH_LIMIT: usize = 1000;
+
+struct FolderScanner {
+    target_dir: PathBuf,
+    num_folders: usize,
+    results: Vec<FolderInfo>,
+    scanning: bool,
+    scan_time: f64,
+    error: Option<String>,
+    progress: ScanProgress,
+    // Events from the running scan or import, drained every frame
+    scan_events: Option<Receiver<ScanEvent>>,
+    cancel_scan: Arc<AtomicBool>,
+    target_dir_input: String,
+    dark_mode: bool,
+    chart_view: ChartView,
+    treemap_color: TreemapColor,
+    sunburst_rings: usize,
+    icicle_search: String,
+    show_about: bool,
+    duplicates: Option<DuplicateReport>,
+    // The report of the running duplicate search
+    duplicate_events: Option<Receiver<DuplicateReport>>,
+    show_duplicates: bool,
+    dedupe_selected: HashSet<usize>,
+    dedupe_mode: LinkMode,
+    dedupe_plan: Option<Vec<Replacement>>,
+    dedupe_outcome: Option<DedupeOutcome>,
+    // The outcome of the running dedupe
+    dedupe_events: Option<Receiver<DedupeOutcome>>,
+    empty_report: Option<EmptyReport>,
+    empty_selected: HashSet<usize>,
+    show_empty_report: bool,
+    confirm_empty_delete: bool,
+    empty_delete_errors: Vec<(PathBuf, String)>,
+    allocation_report: Option<Vec<AllocationEntry>>,
+    show_allocation_report: bool,
+    scan_options: ScanOptions,
+    // Set when the results come from an imported file instead of a scan
+    loaded_from: Option<PathBuf>,
+    worker_error: Arc<Mutex<Option<String>>>,
+    scan_info: Arc<Mutex<Option<ScanInfo>>>,
+    show_chart_export: bool,
+    chart_export_size: [u32; 2],
+    chart_export_svg: bool,
+    // Folders entered from the results table, outermost first
+    drill_stack: Vec<FolderInfo>,
+    // Row picked in the table or a chart, shared between them
+    selected: Option<PathBuf>,
+    scroll_to_selected: bool,
+    // Row under the pointer in a chart this frame, highlighted in the table
+    chart_hovered: Option<PathBuf>,
+    // Legend entry under the pointer last frame, highlighted in the pie
+    pie_legend_hovered: Option<usize>,
+    sort_column: SortColumn,
+    sort_descending: bool,
+    row_details: RowDetailsCache,
+    // Rows open in place in tree mode
+    tree_table: bool,
+    expanded: HashSet<PathBuf>,
+    // The table rows in display order, rebuilt when what they come from changes
+    table_cache: Option<TableCache>,
+    search_text: String,
+    search_mode: SearchMode,
+    search_error: Option<String>,
+    // Bumped for every search so results of outdated ones are dropped
+    search_generation: Arc<AtomicUsize>,
+    search_results: Arc<Mutex<Option<(usize, SearchResults)>>>,
+}
+
+#[derive(Default)]
+struct ScanProgress {
+    current: usize,
+    total: usize,
+    current_path: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChartView {
+    Bar,
+    Pie,
+    Treemap,
+    Icicle,
+}
+
+impl ChartView {
+    // Charts that can be saved as an image, drawn the way the view shows them
+    fn export_kind(self, rings: usize) -> Option<ChartKind> {
+        match self {
+            ChartView::Bar => Some(ChartKind::Bar),
+            ChartView::Pie => Some(ChartKind::Sunburst { rings }),
+            ChartView::Treemap | ChartView::Icicle => None,
+        }
+    }
+}
+
+// Columns of the results table. Sorting by percentage is sorting by size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Name,
+    Size,
+    Percent,
+    Files,
+    Modified,
+    Owner,
+}
+
+// A row of the results table: an entry of the view or, in tree mode, an
+// expanded descendant of one
+struct TableEntry {
+    info: FolderInfo,
+    depth: usize,
+    // Part of the parent row's size, or of the view's at the top level
+    share: f64,
+}
+
+#[derive(Debug, Clone)]
+struct RowDetails {
+    files: u64,
+    owner: Option<String>,
+}
+
+// File counts and owners of table rows, looked up once per path on the
+// rayon pool so the table never waits on the disk. Replaced as a whole when
+// the results change; lookups still running then fill the old map.
+#[derive(Default)]
+struct RowDetailsCache {
+    found: Arc<Mutex<HashMap<PathBuf, RowDetails>>>,
+    requested: HashSet<PathBuf>,
+}
+
+impl RowDetailsCache {
+    fn get(&self, path: &Path) -> Option<RowDetails> {
+        self.found.lock().unwrap().get(path).cloned()
+    }
+
+    fn found(&self) -> usize {
+        self.found.lock().unwrap().len()
+    }
+
+    fn pending(&self) -> bool {
+        self.requested.len() > self.found()
+    }
+
+    // Starts looking up the rows that weren't asked for before. Owners are
+    // only read for results that are on this machine.
+    fn request<'a>(&mut self, rows: impl IntoIterator<Item = &'a FolderInfo>, with_owner: bool) {
+        let mut missing = Vec::new();
+        for info in rows {
+            if !self.requested.contains(&info.path) {
+                self.requested.insert(info.path.clone());
+                missing.push(info.clone());
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+        let found = self.found.clone();
+        rayon::spawn(move || {
+            for info in missing {
+                let details = RowDetails {
+                    files: info.node.file_count(),
+                    owner: if with_owner { owner::owner(&info.path) } else { None },
+                };
+                found.lock().unwrap().insert(info.path, details);
+            }
+        });
+    }
+}
+
+// The results table as last built, with everything it was built from
+struct TableCache {
+    view: Vec<FolderInfo>,
+    sort_column: SortColumn,
+    sort_descending: bool,
+    tree_table: bool,
+    expanded: HashSet<PathBuf>,
+    // Details found so far, when the order depends on them
+    details_found: usize,
+    rows: Vec<TableEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImportFormat {
+    Json,
+    Ncdu,
+    Listing,
+}
+
+impl Default for FolderScanner {
+    fn default() -> Self {
+        let current_dir = std::env::current_dir().unwrap();
+        Self {
+            target_dir: current_dir.clone(),
+            num_folders: 10,
+            results: Vec::new(),
+            scanning: false,
+            scan_time: 0.0,
+            error: None,
+            progress: ScanProgress::default(),
+            scan_events: None,
+            cancel_scan: Arc::new(AtomicBool::new(false)),
+            target_dir_input: current_dir.display().to_string(),
+            dark_mode: true,
+            chart_view: ChartView::Bar,
+            treemap_color: TreemapColor::TopFolder,
+            sunburst_rings: 3,
+            icicle_search: String::new(),
+            show_about: false,
+            duplicates: None,
+            duplicate_events: None,
+            show_duplicates: false,
+            dedupe_selected: HashSet::new(),
+            dedupe_mode: LinkMode::HardLink,
+            dedupe_plan: None,
+            dedupe_outcome: None,
+            dedupe_events: None,
+            empty_report: None,
+            empty_selected: HashSet::new(),
+            show_empty_report: false,
+            confirm_empty_delete: false,
+            empty_delete_errors: Vec::new(),
+            allocation_report: None,
+            show_allocation_report: false,
+            scan_options: ScanOptions::default(),
+            loaded_from: None,
+            worker_error: Arc::new(Mutex::new(None)),
+            scan_info: Arc::new(Mutex::new(None)),
+            show_chart_export: false,
+            chart_export_size: [1600, 900],
+            chart_export_svg: false,
+            drill_stack: Vec::new(),
+            selected: None,
+            scroll_to_selected: false,
+            chart_hovered: None,
+            pie_legend_hovered: None,
+            sort_column: SortColumn::Size,
+            sort_descending: true,
+            row_details: RowDetailsCache::default(),
+            tree_table: false,
+            expanded: HashSet::new(),
+            table_cache: None,
+            search_text: String::new(),
+            search_mode: SearchMode::Substring,
+            search_error: None,
+            search_generation: Arc::new(AtomicUsize::new(0)),
+            search_results: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl FolderScanner {
+    fn scan(&mut self) -> Result<(), String> {
+        self.error = None;
+        
+        // Validate the target directory
+        let path = PathBuf::from(&self.target_dir_input);
+        if !path.exists() || !path.is_dir() {
+            return Err(format!("Invalid directory: {}", self.target_dir_input));
+        }
+        self.target_dir = path;
+        
+        self.scanning = true;
+        self.loaded_from = None;
+        self.progress = ScanProgress::default();
+        self.clear_results();
+
+        let (sender, events) = mpsc::channel();
+        self.cancel_scan = Arc::new(AtomicBool::new(false));
+        let scanner = Scanner::new(&self.target_dir)
+            .options(self.scan_options.clone())
+            .cancel_flag(self.cancel_scan.clone());
+        rayon::spawn(move || scanner.scan_with_events(sender));
+        self.scan_events = Some(events);
+
+        Ok(())
+    }
+
+    fn clear_results(&mut self) {
+        self.results.clear();
+        *self.scan_info.lock().unwrap() = None;
+        self.duplicates = None;
+        // A search still running would report on the old results
+        self.duplicate_events = None;
+        self.show_duplicates = false;
+        self.dedupe_selected.clear();
+        self.dedupe_plan = None;
+        self.empty_report = None;
+        self.show_empty_report = false;
+        self.allocation_report = None;
+        self.show_allocation_report = false;
+        self.drill_stack.clear();
+        self.selected = None;
+        self.row_details = RowDetailsCache::default();
+        self.expanded.clear();
+        self.search_text.clear();
+        self.search_error = None;
+        *self.search_results.lock().unwrap() = None;
+    }
+
+    // Loads results from a file in the background, in place of a live scan.
+    // The outcome arrives on the same event channel as a scan's.
+    fn import_file(&mut self, file: PathBuf, format: ImportFormat) {
+        self.error = None;
+        self.scanning = true;
+        self.loaded_from = Some(file.clone());
+        self.progress = ScanProgress::default();
+        self.clear_results();
+
+        let (sender, events) = mpsc::channel();
+        let worker_error = self.worker_error.clone();
+        rayon::spawn(move || {
+            let loaded = fs::File::open(&file).and_then(|f| match format {
+                ImportFormat::Json => {
+                    let (info, folders) = export::read_json(std::io::BufReader::new(f))?;
+                    Ok(ScanReport { folders, info })
+                }
+                ImportFormat::Ncdu => {
+                    let (root, folders) = ncdu::read_export(f)?;
+                    Ok(imported_report(root, folders))
+                }
+                ImportFormat::Listing => {
+                    let listing = listing::read_listing(std::io::BufReader::new(f))?;
+                    if listing.malformed_count > 0 {
+                        let examples: Vec<String> = listing
+                            .malformed
+                            .iter()
+                            .map(|(line_no, line)| format!("line {}: \"{}\"", line_no, line))
+                            .collect();
+                        *worker_error.lock().unwrap() = Some(format!(
+                            "Skipped {} malformed lines, such as {}",
+                            listing.malformed_count,
+                            examples.join(", ")
+                        ));
+                    }
+                    Ok(imported_report(listing.root, listing.folders))
+                }
+            });
+            let _ = sender.send(match loaded {
+                Ok(report) => ScanEvent::Finished(report),
+                Err(e) => ScanEvent::Failed(ScanError {
+                    path: file,
+                    message: e.to_string(),
+                }),
+            });
+        });
+        self.scan_events = Some(events);
+    }
+
+    fn poll_scan_events(&mut self) {
+        let Some(events) = self.scan_events.take() else {
+            return;
+        };
+        let mut done = false;
+        for event in events.try_iter() {
+            match event {
+                ScanEvent::Started { folders, .. } => self.progress.total = folders,
+                ScanEvent::DirectoryEntered(path) => self.progress.current_path = path.display().to_string(),
+                ScanEvent::FolderCompleted(folder) => {
+                    self.progress.current += 1;
+                    // Show folders as they come in, largest first
+                    let at = self.results.partition_point(|other| other.size >= folder.size);
+                    self.results.insert(at, folder);
+                }
+                ScanEvent::Error(_) => self.progress.current += 1,
+                ScanEvent::Finished(report) => {
+                    if let (Some(started), Some(finished)) = (report.info.started, report.info.finished) {
+                        self.scan_time = finished.duration_since(started).unwrap_or_default().as_secs_f64();
+                    }
+                    if self.loaded_from.is_some() {
+                        self.target_dir_input = report.info.root.display().to_string();
+                        self.target_dir = report.info.root.clone();
+                    }
+                    self.results = report.folders;
+                    self.row_details = RowDetailsCache::default();
+                    *self.scan_info.lock().unwrap() = Some(report.info);
+                    done = true;
+                }
+                ScanEvent::Cancelled => {
+                    self.clear_results();
+                    done = true;
+                }
+                ScanEvent::Failed(error) => {
+                    self.error = Some(format!("Could not read {}: {}", error.path.display(), error.message));
+                    done = true;
+                }
+            }
+        }
+        if done {
+            self.scanning = false;
+            if let Some(error) = self.worker_error.lock().unwrap().take() {
+                self.error = Some(error);
+            }
+        } else {
+            self.scan_events = Some(events);
+        }
+    }
+
+    fn export_json(&mut self) {
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("scan.json")
+            .save_file()
+        else {
+            return;
+        };
+        let info = self.current_scan_info();
+        let written = fs::File::create(&file)
+            .and_then(|f| export::write_json(f, &info, &self.results));
+        if let Err(e) = written {
+            self.error = Some(format!("Could not export to {}: {}", file.display(), e));
+        }
+    }
+
+    fn export_html_report(&mut self) {
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter("HTML", &["html", "htm"])
+            .set_file_name("folder-size-report.html")
+            .save_file()
+        else {
+            return;
+        };
+        let info = self.current_scan_info();
+        let written = fs::File::create(&file)
+            .and_then(|f| html_report::write_html_report(f, &info, &self.results, self.num_folders));
+        if let Err(e) = written {
+            self.error = Some(format!("Could not export to {}: {}", file.display(), e));
+        }
+    }
+
+    fn current_scan_info(&self) -> ScanInfo {
+        // Only missing while a scan is still running
+        self.scan_info.lock().unwrap().clone().unwrap_or_else(|| ScanInfo {
+            root: self.target_dir.clone(),
+            started: None,
+            finished: None,
+            options: ScanOptions::default(),
+            errors: Vec::new(),
+        })
+    }
+
+    fn export_ncdu(&mut self) {
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter("ncdu export", &["json"])
+            .set_file_name("scan.ncdu.json")
+            .save_file()
+        else {
+            return;
+        };
+        let written = fs::File::create(&file)
+            .and_then(|f| ncdu::write_export(f, &self.target_dir, &self.results));
+        if let Err(e) = written {
+            self.error = Some(format!("Could not export to {}: {}", file.display(), e));
+        }
+    }
+
+    fn export_folded(&mut self) {
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter("Folded stacks", &["folded", "txt"])
+            .set_file_name("scan.folded")
+            .save_file()
+        else {
+            return;
+        };
+        let written = fs::File::create(&file).and_then(|f| export::write_folded(f, &self.results));
+        if let Err(e) = written {
+            self.error = Some(format!("Could not export to {}: {}", file.display(), e));
+        }
+    }
+
+    fn export_csv(&mut self, view: &[FolderInfo]) {
+        let default_name = match self.drill_stack.last() {
+            Some(folder) => format!("{}.csv", folder.node.name),
+            None => "folder-sizes.csv".to_string(),
+        };
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(&default_name)
+            .save_file()
+        else {
+            return;
+        };
+        // Shares of the view, the same as in the results table
+        let total = view.iter().map(|info| info.size).sum();
+        let written = fs::File::create(&file).and_then(|f| export::write_csv(f, view, total));
+        if let Err(e) = written {
+            self.error = Some(format!("Could not export to {}: {}", file.display(), e));
+        }
+    }
+
+    fn find_duplicates(&mut self) {
+        if self.loaded_from.is_some() {
+            return;
+        }
+        let results = self.results.clone();
+        self.show_duplicates = true;
+        self.dedupe_selected.clear();
+        self.dedupe_plan = None;
+        self.dedupe_outcome = None;
+
+        let (sender, report) = mpsc::channel();
+        rayon::spawn(move || {
+            let _ = sender.send(duplicates::find_duplicates(&results));
+        });
+        self.duplicate_events = Some(report);
+    }
+
+    fn start_search(&mut self) {
+        let generation = self.search_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        self.search_error = None;
+        if self.search_text.is_empty() {
+            return;
+        }
+        let matcher = match Matcher::new(&self.search_text, self.search_mode) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                self.search_error = Some(e);
+                return;
+            }
+        };
+        let results = self.results.clone();
+        let current = self.search_generation.clone();
+        let found = self.search_results.clone();
+        rayon::spawn(move || {
+            let hits = search::search(&results, &matcher, SEARCH_LIMIT);
+            if current.load(Ordering::Relaxed) == generation {
+                *found.lock().unwrap() = Some((generation, hits));
+            }
+        });
+    }
+
+    // Drills down to the folder that contains `path` and selects it there
+    fn reveal(&mut self, path: &Path) {
+        let Some(top) = self.results.iter().find(|info| path.starts_with(&info.path)) else {
+            return;
+        };
+        let Ok(rest) = path.strip_prefix(&top.path) else {
+            return;
+        };
+        self.drill_stack.clear();
+        let mut current = top.clone();
+        for name in rest.iter() {
+            let name = name.to_string_lossy();
+            let Some(child) = current.node.children.iter().find(|child| *child.name == *name) else {
+                break;
+            };
+            let child = FolderInfo {
+                path: current.path.join(&child.name),
+                size: child.size,
+                node: child.clone(),
+            };
+            self.drill_stack.push(std::mem::replace(&mut current, child));
+        }
+        self.selected = Some(path.to_path_buf());
+        self.scroll_to_selected = true;
+    }
+
+    // Rows for the folder currently shown: the scan results at the top level,
+    // otherwise the children of the innermost drilled-into folder
+    fn current_view(&self, results: &[FolderInfo]) -> Vec<FolderInfo> {
+        let Some(parent) = self.drill_stack.last() else {
+            return results.to_vec();
+        };
+        let mut view = child_rows(parent);
+        view.sort_by_key(|info| Reverse(info.size));
+        view
+    }
+
+    fn select_row(&mut self, row: &FolderInfo) {
+        self.selected = Some(row.path.clone());
+        self.scroll_to_selected = true;
+    }
+
+    // Drills down along `chain`, which starts at one of the `view` rows.
+    // A file zooms to the folder that contains it.
+    fn zoom_to(&mut self, view: &[FolderInfo], chain: Vec<Arc<FsNode>>) {
+        let Some(first) = chain.first() else {
+            return;
+        };
+        let Some(row) = view.iter().find(|info| Arc::ptr_eq(&info.node, first)) else {
+            return;
+        };
+        let mut path = row.path.clone();
+        for (depth, node) in chain.into_iter().enumerate() {
+            if depth > 0 {
+                path = path.join(&node.name);
+            }
+            if !node.is_browsable() {
+                break;
+            }
+            self.drill_stack.push(FolderInfo {
+                path: path.clone(),
+                size: node.size,
+                node,
+            });
+        }
+    }
+
+    fn find_empty_items(&mut self) {
+        self.empty_report = Some(cleanup::find_empty(&self.results));
+        self.empty_selected.clear();
+        self.empty_delete_errors.clear();
+        self.confirm_empty_delete = false;
+        self.show_empty_report = true;
+    }
+
+    fn delete_selected_empty_items(&mut self) {
+        if self.loaded_from.is_some() {
+            return;
+        }
+        let Some(report) = self.empty_report.as_mut() else {
+            return;
+        };
+        self.empty_delete_errors.clear();
+
+        let mut kept = Vec::new();
+        let mut removed = Vec::new();
+        for (i, item) in report.items.drain(..).enumerate() {
+            if !self.empty_selected.contains(&i) {
+                kept.push(item);
+                continue;
+            }
+            match cleanup::remove_empty(&item) {
+                Ok(()) => removed.push(item.path),
+                Err(e) => {
+                    self.empty_delete_errors.push((item.path.clone(), e.to_string()));
+                    kept.push(item);
+                }
+            }
+        }
+        report.items = kept;
+        report.recount();
+        self.empty_selected.clear();
+        self.remove_from_results(&removed);
+    }
+
+    // Drops deleted files and folders from the scan tree, so the table,
+    // charts and search stop showing them
+    fn remove_from_results(&mut self, paths: &[PathBuf]) {
+        if paths.is_empty() {
+            return;
+        }
+        for path in paths {
+            let Some(at) = self.results.iter().position(|info| path.starts_with(&info.path)) else {
+                continue;
+            };
+            let Ok(rest) = path.strip_prefix(&self.results[at].path) else {
+                continue;
+            };
+            let names: Vec<String> = rest.iter().map(|name| name.to_string_lossy().to_string()).collect();
+            if names.is_empty() {
+                self.results.remove(at);
+                continue;
+            }
+            let folder = &mut self.results[at];
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            Arc::make_mut(&mut folder.node).remove_descendant(&names);
+            folder.size = folder.node.size;
+        }
+        self.results.sort_by_key(|info| Reverse(info.size));
+
+        // The drilled-into folders still hold the old nodes
+        let drilled: Vec<PathBuf> = self.drill_stack.drain(..).map(|folder| folder.path).collect();
+        if let Some(mut current) = drilled
+            .first()
+            .and_then(|first| self.results.iter().find(|info| info.path == *first).cloned())
+        {
+            for path in &drilled[1..] {
+                let Some(child) = child_rows(&current).into_iter().find(|row| row.path == *path) else {
+                    break;
+                };
+                self.drill_stack.push(std::mem::replace(&mut current, child));
+            }
+            self.drill_stack.push(current);
+        }
+        self.row_details = RowDetailsCache::default();
+        self.start_search();
+    }
+
+    fn apply_dedupe(&mut self) {
+        if self.loaded_from.is_some() {
+            return;
+        }
+        let Some(plan) = self.dedupe_plan.take() else {
+            return;
+        };
+        let mode = self.dedupe_mode;
+        self.dedupe_selected.clear();
+
+        let (sender, outcome) = mpsc::channel();
+        rayon::spawn(move || {
+            let _ = sender.send(dedupe::apply(&plan, mode));
+        });
+        self.dedupe_events = Some(outcome);
+    }
+
+    // Picks up the outcomes of a finished duplicate search or dedupe
+    fn poll_duplicate_events(&mut self) {
+        if let Some(report) = self.duplicate_events.as_ref().and_then(|events| events.try_recv().ok()) {
+            self.duplicates = Some(report);
+            self.duplicate_events = None;
+        }
+        if let Some(outcome) = self.dedupe_events.as_ref().and_then(|events| events.try_recv().ok()) {
+            // Linked copies waste nothing anymore, so the report drops them
+            if let Some(report) = self.duplicates.as_mut() {
+                report.remove_copies(&outcome.linked, &self.results);
+            }
+            self.dedupe_outcome = Some(outcome);
+            self.dedupe_events = None;
+        }
+    }
+}
+
+fn child_rows(parent: &FolderInfo) -> Vec<FolderInfo> {
+    parent
+        .node
+        .children
+        .iter()
+        .map(|child| FolderInfo {
+            path: parent.path.join(&child.name),
+            size: child.size,
+            node: child.clone(),
+        })
+        .collect()
+}
+
+// Paints the row highlight behind a table cell
+fn fill_cell(ui: &mut egui::Ui, fill: egui::Color32) {
+    let rect = ui.max_rect().expand2(0.5 * ui.spacing().item_spacing);
+    ui.painter().rect_filled(rect, 0.0, fill);
+}
+
+// Cuts long names to `max` characters, ending in an ellipsis
+fn short_name(name: &str, max: usize) -> String {
+    if name.chars().count() <= max {
+        return name.to_string();
+    }
+    let mut short: String = name.chars().take(max - 1).collect();
+    short.push('…');
+    short
+}
+
+// Imported ncdu dumps and listings carry no scan metadata
+fn imported_report(root: PathBuf, folders: Vec<FolderInfo>) -> ScanReport {
+    ScanReport {
+        folders,
+        info: ScanInfo {
+            root,
+            started: None,
+            finished: None,
+            options: ScanOptions::default(),
+            errors: Vec::new(),
+        },
+    }
+}
+
+impl eframe::App for FolderScanner {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_scan_events();
+        self.poll_duplicate_events();
+
+        // Set theme
+        if self.dark_mode {
+            ctx.set_visuals(egui::Visuals::dark());
+        } else {
+            ctx.set_visuals(egui::Visuals::light());
+        }
+
+        // Set custom style
+        let mut style = (*ctx.style()).clone();
+        style.spacing.item_spacing = egui::vec2(10.0, 10.0);
+        style.spacing.window_margin = egui::style::Margin::same(12.0);
+        style.spacing.button_padding = egui::vec2(8.0, 4.0);
+        style.visuals.widgets.noninteractive.rounding = egui::Rounding::same(4.0);
+        style.visuals.widgets.inactive.rounding = egui::Rounding::same(4.0);
+        style.visuals.widgets.active.rounding = egui::Rounding::same(4.0);
+        style.visuals.widgets.hovered.rounding = egui::Rounding::same(4.0);
+        style.visuals.window_rounding = egui::Rounding::same(6.0);
+        ctx.set_style(style);
+
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                ui.heading("Folder Size Analyzer");
+                
+                // Add flexible space to push the buttons to the right
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    // Theme switch
+                    let theme_text = if self.dark_mode { "☀ Light" } else { "🌙 Dark" };
+                    if ui.button(theme_text).clicked() {
+                        self.dark_mode = !self.dark_mode;
+                    }
+                    ui.add_space(5.0);
+                    
+                    // About button
+                    if ui.button("ℹ About").clicked() {
+                        self.show_about = !self.show_about;
+                    }
+                });
+            });
+            ui.add_space(4.0);
+        });
+
+        // Check if we need to show the about dialog
+        if self.show_about {
+            egui::Window::new("About")
+                .collapsible(false)
+                .resizable(true)
+                .min_width(600.0)
+                .min_height(300.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("About Me");
+                        ui.add_space(ui.available_width() - 100.0);
+                        if ui.button("Close").clicked() {
+                            self.show_about = false;
+                        }
+                    });
+                    ui.add_space(5.0);
+                    
+                    ui.label("Hi! I'm Ivar Avello (aka argos3dworld). I'm building open-source tools to improve system management and other random stuff. Currently, I'm working on a folder size scanning tool for Windows that visually tracks storage usage, helping users identify what's taking up space. The project is released under the MIT License, allowing developers worldwide to contribute and expand its capabilities.");
+                    ui.add_space(10.0); 
+                    
+                    ui.label("I'm looking to connect with fellow developers and raise funds to bring this tool to more platforms and enhance its features. If you'd like to support the project, consider donating:");
+                    ui.add_space(10.0);
+                    
+                    // Cryptocurrency donation addresses
+                    ui.group(|ui| {
+                        ui.label("BTC: bc1p5dap9ffeumg82mm4vsv6zhks4zdsv5mvjs85kvjmn72lfh58plqsg3g9c5");
+                        if ui.button("📋 Copy BTC Address").clicked() {
+                            ui.output_mut(|o| o.copied_text = "bc1p5dap9ffeumg82mm4vsv6zhks4zdsv5mvjs85kvjmn72lfh58plqsg3g9c5".to_string());
+                        }
+                    });
+                    
+                    ui.group(|ui| {
+                        ui.label("ETH: 0xD03ff9f2d25Cc43b60076baB3F4D1a2b07501Dfb");
+                        if ui.button("📋 Copy ETH Address").clicked() {
+                            ui.output_mut(|o| o.copied_text = "0xD03ff9f2d25Cc43b60076baB3F4D1a2b07501Dfb".to_string());
+                        }
+                    });
+                    
+                    ui.group(|ui| {
+                        ui.label("SOL: FyRJhuRNRQuMQRawgkJmYrsjUS7K93buWqqoWftYwJMP");
+                        if ui.button("📋 Copy SOL Address").clicked() {
+                            ui.output_mut(|o| o.copied_text = "FyRJhuRNRQuMQRawgkJmYrsjUS7K93buWqqoWftYwJMP".to_string());
+                        }
+                    });
+                    
+                    ui.group(|ui| {
+                        ui.label("TON: UQCJmfmBxgVWNIUC_zAEJzAog9sQ2pZWWiNQ7IC72sBAG1Ij");
+                        if ui.button("📋 Copy TON Address").clicked() {
+                            ui.output_mut(|o| o.copied_text = "UQCJmfmBxgVWNIUC_zAEJzAog9sQ2pZWWiNQ7IC72sBAG1Ij".to_string());
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    // GitHub repository link
+                    ui.horizontal(|ui| {
+                        ui.label("GitHub Repository:");
+                        ui.hyperlink_to("https://github.com/ivar-avello/folder-size-analyzer", "https://github.com/ivar-avello/folder-size-analyzer");
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    // Close button
+                });
+        }
+
+        if self.show_duplicates {
+            self.render_duplicates_window(ctx);
+        }
+
+        if self.show_empty_report {
+            self.render_empty_report_window(ctx);
+        }
+
+        if self.show_allocation_report {
+            self.render_allocation_window(ctx);
+        }
+
+        if self.show_chart_export {
+            self.render_chart_export_window(ctx);
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.add_space(8.0);
+            egui::Frame::none()
+                .fill(ui.visuals().extreme_bg_color)
+                .inner_margin(egui::style::Margin::same(12.0))
+                .rounding(egui::Rounding::same(6.0))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong("Directory:");
+                        ui.add_space(4.0);
+                        
+                        // Directory text input with improved styling
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.target_dir_input)
+                                .hint_text("Enter directory path...")
+                                .desired_width(ui.available_width() - 120.0)
+                        );
+                        
+                        // Handle Enter key press
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            let path = PathBuf::from(&self.target_dir_input);
+                            if path.is_dir() {
+                                self.target_dir = path;
+                                self.error = None;
+                            } else {
+                                self.error = Some(format!("Invalid directory: {}", self.target_dir_input));
+                            }
+                        }
+                        
+                        // Handle Ctrl+V for paste
+                        if response.has_focus() && ui.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::V)) {
+                            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                                if let Ok(text) = clipboard.get_text() {
+                                    self.target_dir_input = text;
+                                }
+                            }
+                        }
+                    });
+                    
+                    ui.add_space(4.0);
+                    
+                    // Place buttons below the directory input
+                    ui.horizontal(|ui| {
+                        // Browse button
+                        if ui.button("📂 Browse").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                self.target_dir_input = path.display().to_string();
+                                self.target_dir = path;
+                                self.error = None;
+                            }
+                        }
+                        
+                        // Scan button
+                        let scan_button = egui::Button::new(
+                            if self.scanning { "⏳ Scanning..." } else { "🔍 Scan" }
+                        ).min_size(egui::vec2(100.0, 0.0));
+                        
+                        if ui.add_enabled(!self.scanning, scan_button).clicked() {
+                            match self.scan() {
+                                Ok(_) => {},
+                                Err(e) => self.error = Some(e),
+                            }
+                        }
+
+                        ui.add_enabled_ui(!self.scanning, |ui| {
+                            ui.menu_button("📥 Import", |ui| {
+                                if ui.button("Analyzer JSON export...").clicked() {
+                                    ui.close_menu();
+                                    if let Some(file) = rfd::FileDialog::new()
+                                        .add_filter("JSON", &["json"])
+                                        .pick_file()
+                                    {
+                                        self.import_file(file, ImportFormat::Json);
+                                    }
+                                }
+                                if ui.button("ncdu JSON dump...")
+                                    .on_hover_text("A file written by `ncdu -o`")
+                                    .clicked()
+                                {
+                                    ui.close_menu();
+                                    if let Some(file) = rfd::FileDialog::new()
+                                        .add_filter("ncdu export", &["json"])
+                                        .add_filter("All files", &["*"])
+                                        .pick_file()
+                                    {
+                                        self.import_file(file, ImportFormat::Ncdu);
+                                    }
+                                }
+                                if ui.button("File listing...")
+                                    .on_hover_text(
+                                        "Output of `find -printf '%s %p\\n'` or `du -ab`. Empty folders are \
+                                         only kept when listed with a trailing `/`",
+                                    )
+                                    .clicked()
+                                {
+                                    ui.close_menu();
+                                    if let Some(file) = rfd::FileDialog::new()
+                                        .add_filter("Text listing", &["txt", "lst", "log"])
+                                        .add_filter("All files", &["*"])
+                                        .pick_file()
+                                    {
+                                        self.import_file(file, ImportFormat::Listing);
+                                    }
+                                }
+                            });
+                        });
+                    });
+                    
+                    // Show error message if any
+                    if let Some(error) = &self.error {
+                        ui.add_space(4.0);
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("⚠").color(egui::Color32::RED));
+                            ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+                        });
+                    }
+                });
+            
+            ui.add_space(8.0);
+            
+            ui.horizontal(|ui| {
+                ui.label("Number of folders to show:");
+                ui.add(egui::DragValue::new(&mut self.num_folders)
+                    .clamp_range(1..=50)
+                    .speed(1.0));
+                ui.add_space(8.0);
+                ui.checkbox(&mut self.scan_options.look_inside_archives, "Look inside archives")
+                    .on_hover_text("List the contents of .zip, .tar, .tar.gz and .tar.zst files as virtual folders");
+            });
+            
+            ui.separator();
+            
+            if self.scanning {
+                let progress = &self.progress;
+                
+                // Show progress bar
+                if progress.total > 0 {
+                    let fraction = progress.current as f32 / progress.total as f32;
+                    ui.add(egui::ProgressBar::new(fraction)
+                        .show_percentage()
+                        .animate(true));
+                    
+                    ui.label(format!(
+                        "Scanning {}/{}: {}",
+                        progress.current,
+                        progress.total,
+                        progress.current_path
+                    ));
+                } else {
+                    ui.spinner();
+                    ui.label("Preparing scan...");
+                }
+                if self.loaded_from.is_none() && ui.button("✖ Cancel").clicked() {
+                    self.cancel_scan.store(true, Ordering::Relaxed);
+                }
+            }
+            
+            self.render_results_ui(ui);
+        });
+
+        if self.scanning {
+            ctx.request_repaint();
+        }
+
+        if self.duplicate_events.is_some() || self.dedupe_events.is_some() {
+            ctx.request_repaint();
+        }
+
+        if self.row_details.pending() {
+            ctx.request_repaint();
+        }
+    }
+}
+
+impl FolderScanner {
+    fn show_size_chart(&mut self, ui: &mut egui::Ui, view: &[FolderInfo], available_width: f32, available_height: f32) {
+        let total_size: u64 = view.iter().map(|i| i.size).sum();
+        if total_size == 0 {
+            return;
+        }
+        let rows: Vec<&FolderInfo> = view.iter().take(self.num_folders).collect();
+        let names: Vec<String> = rows.iter().map(|info| info.node.name.clone()).collect();
+        let selected = rows.iter().position(|info| Some(&info.path) == self.selected.as_ref());
+
+        let plot = egui::plot::Plot::new("sizes")
+            .height(available_height)
+            .width(available_width)
+            // Clicks pick bars, so the plot itself stays put
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .allow_double_click_reset(false)
+            .x_grid_spacer(egui::plot::uniform_grid_spacer(|_| [1.0, 1.0, 1.0]))
+            .x_axis_formatter(move |x, _| {
+                let index = x.round();
+                if (x - index).abs() > 0.01 || index < 0.0 {
+                    return String::new();
+                }
+                names.get(index as usize).map(|name| short_name(name, 14)).unwrap_or_default()
+            })
+            .y_axis_formatter(|y, _| format!("{:.1} GB", y));
+        let response = plot.show(ui, |plot_ui| {
+            // The bar under the pointer, anywhere in its column
+            let hovered = plot_ui.pointer_coordinate().and_then(|pointer| {
+                let index = pointer.x.round();
+                ((pointer.x - index).abs() <= 0.3 && index >= 0.0 && (index as usize) < rows.len())
+                    .then_some(index as usize)
+            });
+            let accent = plot_ui.ctx().style().visuals.selection.stroke.color;
+            let bars: Vec<_> = rows
+                .iter()
+                .enumerate()
+                .map(|(i, info)| {
+                    // Always use absolute size in GB
+                    let size_gb = info.size as f64 / 1e9;
+
+                    // Create label with folder name and size
+                    let label = format!(
+                        "{}\n{:.2} GB ({:.1}%)",
+                        info.node.name,
+                        size_gb,
+                        (info.size as f64 / total_size as f64) * 100.0
+                    );
+
+                    let [r, g, b] = svg_chart::palette_color(i);
+                    let mut fill = egui::Color32::from_rgb(r, g, b);
+                    if hovered.is_some_and(|hovered| hovered != i) {
+                        fill = fill.gamma_multiply(0.6);
+                    }
+                    let mut bar = egui::plot::Bar::new(i as f64, size_gb)
+                        .width(0.6)
+                        .name(label)
+                        .fill(fill);
+                    if selected == Some(i) {
+                        bar = bar.stroke(egui::Stroke::new(2.5, accent));
+                    }
+                    bar
+                })
+                .collect();
+
+            // Configure the bar chart
+            let bar_chart = egui::plot::BarChart::new(bars);
+            plot_ui.bar_chart(bar_chart);
+            hovered
+        });
+
+        let Some(index) = response.inner else {
+            return;
+        };
+        let row = rows[index];
+        self.chart_hovered = Some(row.path.clone());
+        // Click selects the row, double-click opens the folder
+        if response.response.double_clicked() && row.node.is_browsable() {
+            self.drill_stack.push(row.clone());
+        } else if response.response.clicked() {
+            self.select_row(row);
+        }
+    }
+
+    fn show_pie_chart(&mut self, ui: &mut egui::Ui, view: &[FolderInfo], available_width: f32, available_height: f32) {
+        let total_size: u64 = view.iter().map(|i| i.size).sum();
+        if total_size == 0 {
+            return;
+        }
+
+        // The largest rows get their own slice, the rest share "Other"
+        let slices = self.num_folders.min(view.len());
+        let colors: Vec<egui::Color32> = (0..slices)
+            .map(|i| {
+                let [r, g, b] = svg_chart::palette_color(i);
+                egui::Color32::from_rgb(r, g, b)
+            })
+            .collect();
+        let selected = view.iter().take(slices).position(|info| Some(&info.path) == self.selected.as_ref());
+
+        let mut legend: Vec<(String, u64, egui::Color32)> = view
+            .iter()
+            .zip(&colors)
+            .map(|(info, color)| (info.node.name.clone(), info.size, *color))
+            .collect();
+        if view.len() > slices {
+            let other_size = view[slices..].iter().map(|i| i.size).sum();
+            legend.push((format!("Other ({} more)", view.len() - slices), other_size, sunburst::other_color()));
+        }
+
+        let side = available_height.min(available_width * 0.6);
+        let legend_hovered = self.pie_legend_hovered.take();
+        let mut clicked = None;
+        let mut zoom = None;
+        ui.horizontal_top(|ui| {
+            // The inner ring is the pie of the rows, outer rings their contents
+            let chart = sunburst::show(
+                ui,
+                view,
+                egui::vec2(side, side),
+                self.sunburst_rings,
+                &colors,
+                legend_hovered,
+                selected,
+            );
+            clicked = chart.clicked;
+            zoom = chart.zoom;
+            self.chart_hovered = chart.hovered.filter(|&index| index < slices).map(|index| view[index].path.clone());
+
+            egui::ScrollArea::vertical()
+                .id_source("pie_legend")
+                .max_height(available_height)
+                .show(ui, |ui| {
+                    for (index, (name, size, color)) in legend.into_iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let (swatch, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                            ui.painter().rect_filled(swatch, 2.0, color);
+                            let mut text = egui::RichText::new(format!(
+                                "{}: {} ({:.1}%)",
+                                name,
+                                format_size(size),
+                                size as f64 / total_size as f64 * 100.0
+                            ));
+                            // Mirror the slice under the pointer
+                            if chart.hovered == Some(index) {
+                                text = text.strong();
+                            }
+                            let response = ui.selectable_label(selected == Some(index), text);
+                            if response.hovered() {
+                                self.pie_legend_hovered = Some(index);
+                            }
+                            if response.clicked() {
+                                clicked = Some(index);
+                            }
+                        });
+                    }
+                });
+        });
+
+        // "Other" has no row of its own to select
+        if let Some(index) = clicked.filter(|&index| index < slices) {
+            self.select_row(&view[index]);
+        }
+        // Double-clicking a segment re-roots the chart at it
+        if let Some(chain) = zoom {
+            self.zoom_to(view, chain);
+        }
+    }
+    
+    fn show_search_results(&mut self, ui: &mut egui::Ui) {
+        let generation = self.search_generation.load(Ordering::Relaxed);
+        let found = self.search_results.lock().unwrap().clone()
+            .filter(|(done, _)| *done == generation)
+            .map(|(_, found)| found);
+        let mut reveal = None;
+
+        egui::Frame::none()
+            .fill(ui.visuals().extreme_bg_color)
+            .inner_margin(egui::style::Margin::same(12.0))
+            .rounding(egui::Rounding::same(6.0))
+            .show(ui, |ui| {
+                let Some(found) = found else {
+                    if self.search_error.is_none() {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Searching...");
+                        });
+                        ui.ctx().request_repaint();
+                    }
+                    return;
+                };
+                if found.total > found.hits.len() {
+                    ui.strong(format!("{} matches, showing the {} largest", found.total, found.hits.len()));
+                } else {
+                    ui.strong(format!("{} matches", found.total));
+                }
+                ui.add_space(4.0);
+
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .max_scroll_height(500.0)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(Column::remainder().at_least(200.0).clip(true))
+                    .column(Column::initial(90.0).at_least(50.0))
+                    .header(22.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Path");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Size");
+                        });
+                    })
+                    .body(|body| {
+                        body.rows(20.0, found.hits.len(), |index, mut row| {
+                            let hit = &found.hits[index];
+                            row.col(|ui| {
+                                ui.label(if hit.node.is_browsable() { "📁" } else { "📄" });
+                                if ui.link(hit.path.display().to_string())
+                                    .on_hover_text("Show in the results")
+                                    .clicked()
+                                {
+                                    reveal = Some(hit.path.clone());
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.label(format_size(hit.size));
+                                });
+                            });
+                        });
+                    });
+            });
+
+        if let Some(path) = reveal {
+            self.search_text.clear();
+            self.start_search();
+            self.reveal(&path);
+        }
+    }
+
+    fn show_results_table(&mut self, ui: &mut egui::Ui, view: &[FolderInfo]) {
+        let cache = match self.table_cache.take() {
+            Some(cache) if self.table_cache_is_current(&cache, view) => cache,
+            _ => self.build_table(view),
+        };
+        let rows = &cache.rows;
+        let mut shown = Vec::new();
+        let selection = ui.visuals().selection.bg_fill;
+
+        let mut table = TableBuilder::new(ui)
+            .striped(true)
+            .max_scroll_height(300.0)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::initial(280.0).at_least(80.0).resizable(true).clip(true))
+            .column(Column::initial(90.0).at_least(50.0).resizable(true))
+            .column(Column::initial(90.0).at_least(40.0).resizable(true))
+            .column(Column::initial(80.0).at_least(40.0).resizable(true))
+            .column(Column::initial(130.0).at_least(60.0).resizable(true).clip(true))
+            .column(Column::remainder().at_least(60.0).clip(true));
+        if self.scroll_to_selected {
+            if let Some(index) = rows.iter().position(|row| Some(&row.info.path) == self.selected.as_ref()) {
+                table = table.scroll_to_row(index, Some(egui::Align::Center));
+            }
+            self.scroll_to_selected = false;
+        }
+
+        let mut sort_by = None;
+        let mut drill_into = None;
+        let mut select = None;
+        let mut toggle = None;
+        table
+            .header(22.0, |mut header| {
+                for (column, title) in [
+                    (SortColumn::Name, "Folder"),
+                    (SortColumn::Size, "Size"),
+                    (SortColumn::Percent, "%"),
+                    (SortColumn::Files, "Files"),
+                    (SortColumn::Modified, "Last modified"),
+                    (SortColumn::Owner, "Owner"),
+                ] {
+                    header.col(|ui| {
+                        let active = self.sort_column == column;
+                        let title = match (active, self.sort_descending) {
+                            (false, _) => title.to_string(),
+                            (true, true) => format!("{} ⬇", title),
+                            (true, false) => format!("{} ⬆", title),
+                        };
+                        if ui.selectable_label(active, egui::RichText::new(title).strong()).clicked() {
+                            sort_by = Some(column);
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(20.0, rows.len(), |index, mut row| {
+                    let TableEntry { info, depth, share } = &rows[index];
+                    let details = self.row_details.get(&info.path);
+                    if details.is_none() {
+                        shown.push(info);
+                    }
+                    let fill = if Some(&info.path) == self.selected.as_ref() {
+                        selection.gamma_multiply(0.4)
+                    } else if Some(&info.path) == self.chart_hovered.as_ref() {
+                        selection.gamma_multiply(0.15)
+                    } else {
+                        egui::Color32::TRANSPARENT
+                    };
+                    let modified = info.node.modified
+                        .map(|time| chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    let files = match &details {
+                        Some(details) if info.node.is_dir => details.files.to_string(),
+                        _ => String::new(),
+                    };
+
+                    let cells = [
+                        row.col(|ui| {
+                            fill_cell(ui, fill);
+                            if self.tree_table {
+                                ui.add_space(*depth as f32 * 16.0);
+                                let (_, arrow) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::click());
+                                if info.node.is_browsable() {
+                                    let open = self.expanded.contains(&info.path);
+                                    egui::collapsing_header::paint_default_icon(ui, if open { 1.0 } else { 0.0 }, &arrow);
+                                    if arrow.clicked() {
+                                        toggle = Some(info.path.clone());
+                                    }
+                                }
+                            }
+                            if ui.small_button("📋").on_hover_text("Copy path").clicked() {
+                                if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                                    let _ = clipboard.set_text(info.path.display().to_string());
+                                }
+                            }
+                            // Folders and opened archives can be drilled into
+                            let name = if info.node.is_browsable() {
+                                ui.add(egui::Link::new(&info.node.name))
+                            } else {
+                                ui.add(egui::Label::new(&info.node.name).wrap(false))
+                            };
+                            if name.clicked() {
+                                drill_into = Some(info.clone());
+                            }
+                            name.on_hover_ui(|ui| {
+                                ui.label(info.path.display().to_string());
+                                if let Some(uncompressed) = info.node.uncompressed {
+                                    ui.label(format!(
+                                        "Compressed: {}, uncompressed: {}",
+                                        format_size(info.size),
+                                        format_size(uncompressed)
+                                    ));
+                                }
+                            });
+                        }),
+                        row.col(|ui| {
+                            fill_cell(ui, fill);
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.label(format_size(info.size));
+                            });
+                        }),
+                        row.col(|ui| {
+                            fill_cell(ui, fill);
+                            // Bar of the share, of the parent row in tree mode
+                            let cell = ui.max_rect().shrink2(egui::vec2(0.0, 4.0));
+                            let bar = egui::Rect::from_min_size(cell.min, egui::vec2(cell.width() * *share as f32, cell.height()));
+                            ui.painter().rect_filled(bar, 2.0, selection.gamma_multiply(0.6));
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.label(format!("{:.1}%", share * 100.0));
+                            });
+                        }),
+                        row.col(|ui| {
+                            fill_cell(ui, fill);
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.label(files);
+                            });
+                        }),
+                        row.col(|ui| {
+                            fill_cell(ui, fill);
+                            ui.label(modified);
+                        }),
+                        row.col(|ui| {
+                            fill_cell(ui, fill);
+                            ui.label(details.and_then(|details| details.owner).unwrap_or_default());
+                        }),
+                    ];
+                    // Clicking anywhere on the row selects it in the charts too
+                    for (_, cell) in cells {
+                        if cell.interact(egui::Sense::click()).clicked() {
+                            select = Some(info.path.clone());
+                        }
+                    }
+                });
+            });
+
+        self.row_details.request(shown, self.loaded_from.is_none());
+        self.table_cache = Some(cache);
+
+        if let Some(column) = sort_by {
+            if self.sort_column == column {
+                self.sort_descending = !self.sort_descending;
+            } else {
+                self.sort_column = column;
+                // Names read best A to Z, everything else largest first
+                self.sort_descending = column != SortColumn::Name && column != SortColumn::Owner;
+            }
+        }
+        if let Some(info) = drill_into {
+            self.drill_stack.push(info);
+        }
+        if select.is_some() {
+            self.selected = select;
+        }
+        if let Some(path) = toggle {
+            if !self.expanded.remove(&path) {
+                self.expanded.insert(path);
+            }
+        }
+    }
+
+    fn table_cache_is_current(&self, cache: &TableCache, view: &[FolderInfo]) -> bool {
+        cache.sort_column == self.sort_column
+            && cache.sort_descending == self.sort_descending
+            && cache.tree_table == self.tree_table
+            && cache.expanded == self.expanded
+            && cache.details_found == self.details_found()
+            && cache.view.len() == view.len()
+            && cache
+                .view
+                .iter()
+                .zip(view)
+                .all(|(cached, info)| cached.path == info.path && Arc::ptr_eq(&cached.node, &info.node))
+    }
+
+    // Details only matter for the order when sorting by them
+    fn details_found(&self) -> usize {
+        match self.sort_column {
+            SortColumn::Files | SortColumn::Owner => self.row_details.found(),
+            _ => 0,
+        }
+    }
+
+    fn build_table(&mut self, view: &[FolderInfo]) -> TableCache {
+        let total_size: u64 = view.iter().map(|i| i.size).sum();
+        let mut rows = Vec::new();
+        self.push_table_rows(&mut rows, view.to_vec(), total_size, 0);
+        TableCache {
+            view: view.to_vec(),
+            sort_column: self.sort_column,
+            sort_descending: self.sort_descending,
+            tree_table: self.tree_table,
+            expanded: self.expanded.clone(),
+            details_found: self.details_found(),
+            rows,
+        }
+    }
+
+    // Adds `rows` to the table in the current sort order, each followed by
+    // its children when it is expanded in tree mode
+    fn push_table_rows(&mut self, table: &mut Vec<TableEntry>, mut rows: Vec<FolderInfo>, parent_size: u64, depth: usize) {
+        self.sort_rows(&mut rows);
+        for info in rows {
+            let share = if parent_size > 0 {
+                info.size as f64 / parent_size as f64
+            } else {
+                0.0
+            };
+            let expanded = self.tree_table && info.node.is_browsable() && self.expanded.contains(&info.path);
+            let children = if expanded { child_rows(&info) } else { Vec::new() };
+            let size = info.size;
+            table.push(TableEntry { info, depth, share });
+            if expanded {
+                self.push_table_rows(table, children, size, depth + 1);
+            }
+        }
+    }
+
+    // Orders the table rows by the chosen column. Ties keep the size order
+    // of the view. Rows whose details are still being looked up sort as if
+    // they had none, and move once the details arrive.
+    fn sort_rows(&mut self, rows: &mut [FolderInfo]) {
+        let column = self.sort_column;
+        if matches!(column, SortColumn::Files | SortColumn::Owner) {
+            self.row_details.request(rows.iter(), self.loaded_from.is_none());
+        }
+        let details = self.row_details.found.lock().unwrap();
+        rows.sort_by(|a, b| {
+            let order = match column {
+                // Compared a character at a time, without lowercase copies
+                SortColumn::Name => a
+                    .node
+                    .name
+                    .chars()
+                    .flat_map(char::to_lowercase)
+                    .cmp(b.node.name.chars().flat_map(char::to_lowercase)),
+                SortColumn::Size | SortColumn::Percent => a.size.cmp(&b.size),
+                SortColumn::Files => {
+                    let files = |info: &FolderInfo| details.get(&info.path).map(|details| details.files);
+                    files(a).cmp(&files(b))
+                }
+                SortColumn::Modified => a.node.modified.cmp(&b.node.modified),
+                SortColumn::Owner => {
+                    let owner = |info: &FolderInfo| details.get(&info.path).and_then(|details| details.owner.as_deref());
+                    owner(a).cmp(&owner(b))
+                }
+            };
+            if self.sort_descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+    }
+
+    fn render_duplicates_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_duplicates;
+        let mut apply_requested = false;
+        egui::Window::new("Duplicate Files")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(600.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                if self.duplicate_events.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Comparing files...");
+                    });
+                    return;
+                }
+
+                let Some(report) = self.duplicates.as_ref() else {
+                    ui.label("No duplicate scan has been run yet.");
+                    return;
+                };
+
+                ui.label(format!(
+                    "{} duplicate sets among {} files, {} wasted",
+                    report.sets.len(),
+                    report.files_compared,
+                    format_size(report.total_wasted())
+                ));
+                if report.unreadable > 0 {
+                    ui.label(egui::RichText::new(format!("{} files could not be read", report.unreadable))
+                        .color(egui::Color32::YELLOW));
+                }
+                if report.hard_links > 0 {
+                    ui.label(format!("{} hard links to files already listed were left out", report.hard_links));
+                }
+
+                let mut preview_requested = false;
+                let mut cancel_requested = false;
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Replace selected copies with:");
+                    ui.radio_value(&mut self.dedupe_mode, LinkMode::HardLink, LinkMode::HardLink.label());
+                    ui.radio_value(&mut self.dedupe_mode, LinkMode::Reflink, LinkMode::Reflink.label());
+                    let can_preview = self.loaded_from.is_none()
+                        && !self.dedupe_selected.is_empty()
+                        && self.dedupe_events.is_none();
+                    if ui.add_enabled(can_preview, egui::Button::new("👁 Preview")).clicked() {
+                        preview_requested = true;
+                    }
+                });
+
+                if self.dedupe_events.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Verifying and linking files...");
+                    });
+                } else if let Some(outcome) = self.dedupe_outcome.as_ref() {
+                    ui.label(format!(
+                        "Replaced {} files, reclaimed {}",
+                        outcome.replaced,
+                        format_size(outcome.reclaimed)
+                    ));
+                    if outcome.already_linked > 0 {
+                        ui.label(format!("{} files were already hard links and were left as they are", outcome.already_linked));
+                    }
+                    for (path, error) in &outcome.failures {
+                        ui.label(egui::RichText::new(format!("{}: {}", path.display(), error))
+                            .color(egui::Color32::RED));
+                    }
+                }
+
+                if let Some(plan) = &self.dedupe_plan {
+                    ui.group(|ui| {
+                        let reclaimable: u64 = plan.iter().map(|r| r.size).sum();
+                        ui.strong(format!(
+                            "Dry run: {} files would become {}s, reclaiming {}",
+                            plan.len(),
+                            self.dedupe_mode.label().to_lowercase(),
+                            format_size(reclaimable)
+                        ));
+                        ui.label(self.dedupe_mode.metadata_note());
+                        egui::ScrollArea::vertical()
+                            .id_source("dedupe_plan")
+                            .max_height(150.0)
+                            .show(ui, |ui| {
+                                for replacement in plan {
+                                    ui.label(format!(
+                                        "{} → {}",
+                                        replacement.duplicate.display(),
+                                        replacement.original.display()
+                                    ));
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            if ui.button("✔ Apply").clicked() {
+                                apply_requested = true;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                cancel_requested = true;
+                            }
+                        });
+                    });
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::CollapsingHeader::new("Wasted space per folder")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (folder, wasted) in &report.wasted_per_folder {
+                                ui.horizontal(|ui| {
+                                    ui.label(folder.display().to_string());
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                        ui.label(format_size(*wasted));
+                                    });
+                                });
+                            }
+                        });
+
+                    ui.separator();
+
+                    for (i, set) in report.sets.iter().enumerate() {
+                        let id = ui.make_persistent_id(("duplicate_set", i));
+                        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                            .show_header(ui, |ui| {
+                                let mut selected = self.dedupe_selected.contains(&i);
+                                if ui.checkbox(&mut selected, "").changed() {
+                                    if selected {
+                                        self.dedupe_selected.insert(i);
+                                    } else {
+                                        self.dedupe_selected.remove(&i);
+                                    }
+                                    self.dedupe_plan = None;
+                                }
+                                ui.label(format!(
+                                    "{} copies of {} ({} wasted)",
+                                    set.paths.len(),
+                                    format_size(set.size),
+                                    format_size(set.wasted_bytes())
+                                ));
+                            })
+                            .body(|ui| {
+                                for (j, path) in set.paths.iter().enumerate() {
+                                    ui.horizontal(|ui| {
+                                        // The first copy is the one that is kept when linking
+                                        let label = if j == 0 {
+                                            egui::RichText::new(path.display().to_string()).strong()
+                                        } else {
+                                            egui::RichText::new(path.display().to_string())
+                                        };
+                                        ui.label(label);
+                                        if ui.small_button("📋").clicked() {
+                                            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                                                let _ = clipboard.set_text(path.display().to_string());
+                                            }
+                                        }
+                                    });
+                                }
+                            });
+                    }
+                });
+
+                if preview_requested {
+                    let mut selected: Vec<usize> = self.dedupe_selected.iter().copied().collect();
+                    selected.sort_unstable();
+                    self.dedupe_plan = Some(dedupe::plan(selected.iter().filter_map(|&i| report.sets.get(i))));
+                    self.dedupe_outcome = None;
+                }
+                if cancel_requested {
+                    self.dedupe_plan = None;
+                }
+            });
+        self.show_duplicates = open;
+        if apply_requested {
+            self.apply_dedupe();
+        }
+    }
+
+    fn render_empty_report_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_empty_report;
+        let mut delete_requested = false;
+        egui::Window::new("Empty Folders & Zero-byte Files")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(600.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                let Some(report) = &self.empty_report else {
+                    return;
+                };
+
+                let empty_dirs = report.items.iter().filter(|item| item.is_dir).count();
+                ui.label(format!(
+                    "{} empty folders, {} zero-byte files",
+                    empty_dirs,
+                    report.items.len() - empty_dirs
+                ));
+
+                egui::CollapsingHeader::new("Per folder")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::Grid::new("empty_per_folder").striped(true).show(ui, |ui| {
+                            ui.strong("Folder");
+                            ui.strong("Empty folders");
+                            ui.strong("Zero-byte files");
+                            ui.end_row();
+                            for counts in &report.per_folder {
+                                ui.label(counts.folder.display().to_string());
+                                ui.label(counts.empty_dirs.to_string());
+                                ui.label(counts.zero_byte_files.to_string());
+                                ui.end_row();
+                            }
+                        });
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Select all").clicked() {
+                        self.empty_selected = (0..report.items.len()).collect();
+                    }
+                    if ui.button("Select folders").clicked() {
+                        self.empty_selected = report.items.iter().enumerate()
+                            .filter(|(_, item)| item.is_dir)
+                            .map(|(i, _)| i)
+                            .collect();
+                    }
+                    if ui.button("Select files").clicked() {
+                        self.empty_selected = report.items.iter().enumerate()
+                            .filter(|(_, item)| !item.is_dir)
+                            .map(|(i, _)| i)
+                            .collect();
+                    }
+                    if ui.button("Select none").clicked() {
+                        self.empty_selected.clear();
+                    }
+                    let delete_text = format!("🗑 Delete {} selected", self.empty_selected.len());
+                    let can_delete = self.loaded_from.is_none() && !self.empty_selected.is_empty();
+                    if ui
+                        .add_enabled(can_delete, egui::Button::new(delete_text))
+                        .on_disabled_hover_text("Select items from a scan of the files on disk to delete them")
+                        .clicked()
+                    {
+                        self.confirm_empty_delete = true;
+                    }
+                });
+
+                if self.confirm_empty_delete {
+                    ui.group(|ui| {
+                        ui.label(format!(
+                            "Permanently delete {} items? Each one is checked to still be empty first.",
+                            self.empty_selected.len()
+                        ));
+                        ui.horizontal(|ui| {
+                            if ui.button("Delete").clicked() {
+                                delete_requested = true;
+                                self.confirm_empty_delete = false;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                self.confirm_empty_delete = false;
+                            }
+                        });
+                    });
+                }
+
+                for (path, error) in &self.empty_delete_errors {
+                    ui.label(egui::RichText::new(format!("{}: {}", path.display(), error))
+                        .color(egui::Color32::RED));
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, item) in report.items.iter().enumerate() {
+                        let mut selected = self.empty_selected.contains(&i);
+                        let icon = if item.is_dir { "📁" } else { "📄" };
+                        if ui.checkbox(&mut selected, format!("{} {}", icon, item.path.display())).changed() {
+                            if selected {
+                                self.empty_selected.insert(i);
+                            } else {
+                                self.empty_selected.remove(&i);
+                            }
+                        }
+                    }
+                });
+            });
+        self.show_empty_report = open;
+        if delete_requested {
+            self.delete_selected_empty_items();
+        }
+    }
+
+    fn render_allocation_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_allocation_report;
+        egui::Window::new("Sparse & Preallocated Files")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(700.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                let Some(entries) = &self.allocation_report else {
+                    return;
+                };
+
+                let apparent: u64 = self.results.iter().map(|info| info.node.size).sum();
+                let allocated: u64 = self.results.iter().map(|info| info.node.allocated).sum();
+                ui.label(format!(
+                    "Sizes shown in the results are file lengths ({}). On disk the same files take {}.",
+                    format_size(apparent),
+                    format_size(allocated)
+                ));
+                ui.label("Sparse and compressed files use less space than their length; preallocated files reserve more.");
+                ui.separator();
+
+                if entries.is_empty() {
+                    ui.label("No sparse or preallocated files found.");
+                    return;
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("allocation_grid").striped(true).show(ui, |ui| {
+                        ui.strong("File");
+                        ui.strong("Length");
+                        ui.strong("On disk");
+                        ui.strong("Difference");
+                        ui.strong("Kind");
+                        ui.end_row();
+                        for entry in entries {
+                            let name = entry.path.file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_else(|| entry.path.display().to_string());
+                            ui.label(name).on_hover_text(entry.path.display().to_string());
+                            ui.label(format_size(entry.apparent));
+                            ui.label(format_size(entry.allocated));
+                            ui.label(format_size(entry.difference()));
+                            ui.label(entry.kind.label());
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show_allocation_report = open;
+    }
+
+    fn render_chart_export_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_chart_export;
+        let mut save_requested = false;
+        egui::Window::new("Export Chart")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let chart = match self.chart_view {
+                    ChartView::Pie => format!("pie chart with {} rings", self.sunburst_rings),
+                    _ => "bar chart".to_string(),
+                };
+                ui.label(format!("Exports the current {} with its labels and legend.", chart));
+                ui.add_space(4.0);
+                egui::Grid::new("chart_export_grid").show(ui, |ui| {
+                    ui.label("Format:");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.chart_export_svg, false, "PNG");
+                        ui.radio_value(&mut self.chart_export_svg, true, "SVG");
+                    });
+                    ui.end_row();
+                    ui.label("Size (px):");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut self.chart_export_size[0]).clamp_range(200..=8000));
+                        ui.label("×");
+                        ui.add(egui::DragValue::new(&mut self.chart_export_size[1]).clamp_range(150..=8000));
+                    });
+                    ui.end_row();
+                });
+                ui.add_space(4.0);
+                if ui.button("💾 Save...").clicked() {
+                    save_requested = true;
+                }
+            });
+        self.show_chart_export = open;
+
+        if save_requested {
+            let (extension, filter) = if self.chart_export_svg { ("svg", "SVG image") } else { ("png", "PNG image") };
+            let Some(file) = rfd::FileDialog::new()
+                .add_filter(filter, &[extension])
+                .set_file_name(format!("chart.{}", extension))
+                .save_file()
+            else {
+                return;
+            };
+            let view = self.current_view(&self.results);
+            let Some(kind) = self.chart_view.export_kind(self.sunburst_rings) else {
+                return;
+            };
+            let [width, height] = self.chart_export_size;
+            match chart_export::export_chart(&file, kind, &view, self.num_folders, width, height) {
+                Ok(()) => self.show_chart_export = false,
+                Err(e) => self.error = Some(format!("Could not export chart to {}: {}", file.display(), e)),
+            }
+        }
+    }
+
+    fn render_results_ui(&mut self, ui: &mut egui::Ui) {
+        // Get a clone of the results to avoid borrow checker issues
+        let results = self.results.clone();
+        
+        if !results.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                if let Some(file) = &self.loaded_from {
+                    ui.label(format!("Loaded from {}", file.display()));
+                } else if self.scanning {
+                    ui.label(format!("{} folders scanned so far", results.len()));
+                } else {
+                    ui.label(format!("Scan completed in {:.2} seconds", self.scan_time));
+                }
+
+                if let Some(info) = self.scan_info.lock().unwrap().as_ref() {
+                    if !info.errors.is_empty() {
+                        ui.label(egui::RichText::new(format!("⚠ {} folders could not be read", info.errors.len()))
+                            .color(egui::Color32::YELLOW))
+                            .on_hover_ui(|ui| {
+                                for error in &info.errors {
+                                    ui.label(format!("{}: {}", error.path.display(), error.message));
+                                }
+                            });
+                    }
+                }
+                
+                // Add copy path button
+                if ui.button("📋 Copy Path").clicked() {
+                    if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                        let _ = clipboard.set_text(self.target_dir.display().to_string());
+                    }
+                }
+
+                // The analyses and exports need the complete results
+                ui.add_enabled_ui(!self.scanning, |ui| {
+                    // Imported results describe files that may not be on this
+                    // machine, so nothing that reads or changes them is offered
+                    let on_disk = self.loaded_from.is_none();
+                    let finding = self.duplicate_events.is_some();
+                    if ui
+                        .add_enabled(on_disk && !finding, egui::Button::new("🧬 Find Duplicates"))
+                        .on_disabled_hover_text("Needs a scan of the files on disk, not an import")
+                        .clicked()
+                    {
+                        self.find_duplicates();
+                    }
+
+                    if ui.button("🧹 Empty Items").clicked() {
+                        self.find_empty_items();
+                    }
+
+                    if ui.button("💾 Sparse Files").clicked() {
+                        self.allocation_report = Some(sparse::find_allocation_mismatches(&results));
+                        self.show_allocation_report = true;
+                    }
+
+                    ui.menu_button("📤 Export", |ui| {
+                        if ui.button("JSON (full tree)...").clicked() {
+                            ui.close_menu();
+                            self.export_json();
+                        }
+                        if ui.button("ncdu JSON dump...").clicked() {
+                            ui.close_menu();
+                            self.export_ncdu();
+                        }
+                        if ui.button("HTML report...").clicked() {
+                            ui.close_menu();
+                            self.export_html_report();
+                        }
+                        if ui.button("Folded stacks (flame graph)...").clicked() {
+                            ui.close_menu();
+                            self.export_folded();
+                        }
+                    });
+                });
+            });
+            
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                ui.label("🔍");
+                let hint = match self.search_mode {
+                    SearchMode::Expression => "e.g. size > 1G and ext in (log, gz) and mtime < 90d",
+                    _ => "Search names, e.g. *.core or target",
+                };
+                let mut edit = ui.add_enabled(
+                    !self.scanning,
+                    egui::TextEdit::singleline(&mut self.search_text)
+                        .hint_text(hint)
+                        .desired_width(if self.search_mode == SearchMode::Expression { 380.0 } else { 260.0 }),
+                );
+                if self.search_mode == SearchMode::Expression {
+                    edit = edit.on_hover_text(filter::SYNTAX);
+                }
+                let mut changed = edit.changed();
+                egui::ComboBox::from_id_source("search_mode")
+                    .selected_text(self.search_mode.to_string())
+                    .show_ui(ui, |ui| {
+                        for mode in [SearchMode::Substring, SearchMode::Glob, SearchMode::Regex, SearchMode::Expression] {
+                            changed |= ui.selectable_value(&mut self.search_mode, mode, mode.to_string()).changed();
+                        }
+                    });
+                if !self.search_text.is_empty() && ui.small_button("✖").on_hover_text("Clear search").clicked() {
+                    self.search_text.clear();
+                    changed = true;
+                }
+                if changed {
+                    self.start_search();
+                }
+                if let Some(error) = &self.search_error {
+                    ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+                }
+            });
+            ui.add_space(4.0);
+
+            // Matches replace the normal view until the search is cleared
+            if !self.search_text.is_empty() {
+                self.show_search_results(ui);
+                return;
+            }
+
+            let view = self.current_view(&results);
+            
+            // Results section with improved styling
+            egui::Frame::none()
+                .fill(ui.visuals().extreme_bg_color)
+                .inner_margin(egui::style::Margin::same(12.0))
+                .rounding(egui::Rounding::same(6.0))
+                .show(ui, |ui| {
+                    // Breadcrumb for drill-down navigation
+                    if !self.drill_stack.is_empty() {
+                        ui.horizontal_wrapped(|ui| {
+                            if ui.button("⬆ Up").clicked() {
+                                self.drill_stack.pop();
+                            }
+                            if ui.link(self.target_dir.display().to_string()).clicked() {
+                                self.drill_stack.clear();
+                            }
+                            let mut truncate_to = None;
+                            for (depth, folder) in self.drill_stack.iter().enumerate() {
+                                ui.label("/");
+                                if ui.link(&folder.node.name).clicked() {
+                                    truncate_to = Some(depth + 1);
+                                }
+                            }
+                            if let Some(depth) = truncate_to {
+                                self.drill_stack.truncate(depth);
+                            }
+                        });
+                        ui.add_space(4.0);
+                    }
+
+                    // Size distribution header with chart toggle
+                    ui.horizontal(|ui| {
+                        ui.columns(2, |columns| {
+                            columns[0].strong("Size Distribution");
+                            columns[1].with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                // Right to left, so the last view comes first
+                                ui.selectable_value(&mut self.chart_view, ChartView::Icicle, "🧊 Icicle");
+                                ui.selectable_value(&mut self.chart_view, ChartView::Treemap, "🗺 Treemap");
+                                ui.selectable_value(&mut self.chart_view, ChartView::Pie, "🥧 Pie");
+                                ui.selectable_value(&mut self.chart_view, ChartView::Bar, "📊 Bar");
+                                let exportable = self.chart_view.export_kind(self.sunburst_rings).is_some();
+                                if ui.add_enabled(exportable, egui::Button::new("🖼 Export Chart"))
+                                    .on_disabled_hover_text("Only the bar and pie charts can be exported")
+                                    .clicked()
+                                {
+                                    self.show_chart_export = true;
+                                }
+                                if self.chart_view == ChartView::Pie {
+                                    ui.add(egui::DragValue::new(&mut self.sunburst_rings).clamp_range(1..=6))
+                                        .on_hover_text("Directory levels shown as rings around the pie");
+                                    ui.label("Rings:");
+                                }
+                                if self.chart_view == ChartView::Icicle {
+                                    ui.add(egui::TextEdit::singleline(&mut self.icicle_search)
+                                        .hint_text("Highlight names...")
+                                        .desired_width(140.0));
+                                }
+                                if self.chart_view == ChartView::Treemap {
+                                    egui::ComboBox::from_id_source("treemap_color")
+                                        .selected_text(match self.treemap_color {
+                                            TreemapColor::TopFolder => "Color by folder",
+                                            TreemapColor::Extension => "Color by extension",
+                                        })
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(&mut self.treemap_color, TreemapColor::TopFolder, "Color by folder");
+                                            ui.selectable_value(&mut self.treemap_color, TreemapColor::Extension, "Color by extension");
+                                        });
+                                }
+                            });
+                        });
+                    });
+                    
+                    ui.add_space(8.0);
+                    
+                    // Chart area with dynamic sizing
+                    let available_width = ui.available_width();
+                    let chart_height = 200.0;
+                    
+                    self.chart_hovered = None;
+                    match self.chart_view {
+                        ChartView::Bar => self.show_size_chart(ui, &view, available_width, chart_height),
+                        ChartView::Pie => self.show_pie_chart(ui, &view, available_width, 280.0),
+                        ChartView::Treemap => {
+                            let size = egui::vec2(available_width, 320.0);
+                            if let Some(chain) = treemap::show(ui, &view, size, self.treemap_color) {
+                                self.zoom_to(&view, chain);
+                            }
+                        }
+                        ChartView::Icicle => {
+                            // Tall trees scroll instead of squeezing the rows
+                            let clicked = egui::ScrollArea::vertical()
+                                .id_source("icicle_scroll")
+                                .max_height(320.0)
+                                .show(ui, |ui| icicle::show(ui, &view, available_width, &self.icicle_search))
+                                .inner;
+                            if let Some(chain) = clicked {
+                                self.zoom_to(&view, chain);
+                            }
+                        }
+                    }
+                    
+                    ui.add_space(8.0);
+                    
+                    // Folder list with improved styling
+                    ui.horizontal(|ui| {
+                        ui.strong("Folder Details");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("📄 Export CSV").clicked() {
+                                self.export_csv(&view);
+                            }
+                            ui.selectable_value(&mut self.tree_table, true, "🌲 Tree")
+                                .on_hover_text("Expand folders in place");
+                            ui.selectable_value(&mut self.tree_table, false, "☰ List");
+                        });
+                    });
+                    ui.add_space(4.0);
+                    
+                    self.show_results_table(ui, &view);
+                });
+        }
+    }
+}
+
+/// Opens the window and blocks until it is closed.
+pub fn run() {
+    let options = eframe::NativeOptions {
+        initial_window_size: Some(egui::vec2(800.0, 600.0)),
+        min_window_size: Some(egui::vec2(600.0, 400.0)),
+        follow_system_theme: false,
+        default_theme: eframe::Theme::Dark,
+        centered: true,
+        decorated: true,
+        transparent: false,
+        ..Default::default()
+    };
+    
+    eframe::run_native(
+        "Folder Size Analyzer",
+        options,
+        Box::new(|_cc| Box::new(FolderScanner::default())),
+    ).unwrap();
+}
@@ -0,0 +1,143 @@
+use std::io::{self, Write};
+
+use crate::export::format_timestamp;
+use crate::svg_chart::{self, escape};
+use crate::tree::{FsNode, ScanInfo};
+use crate::{format_size, FolderInfo};
+
+// Keeps reports of huge trees small enough to open in a browser
+const MAX_TREE_DEPTH: usize = 8;
+const MAX_CHILDREN_PER_FOLDER: usize = 200;
+
+const STYLE: &str = "
+body { font-family: 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+table.summary td { padding: 2px 12px 2px 0; }
+.charts { display: flex; flex-wrap: wrap; gap: 24px; margin: 1.5em 0; }
+.tree { font-size: 14px; }
+.tree details { margin-left: 1.2em; }
+.tree > details { margin-left: 0; }
+.tree summary, .tree .file { display: flex; align-items: center; gap: 8px; padding: 1px 0; cursor: default; }
+.tree summary { cursor: pointer; }
+.tree .file { margin-left: 2.4em; }
+.tree .name { flex: 1; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+.tree .size { width: 90px; text-align: right; font-variant-numeric: tabular-nums; }
+.tree .bar { width: 120px; height: 8px; background: #eee; border-radius: 4px; overflow: hidden; }
+.tree .bar span { display: block; height: 100%; background: rgb(25,130,196); }
+.tree .more { margin-left: 2.4em; color: #777; font-style: italic; }
+.errors { color: #b00; }
+";
+
+/// Writes a single self-contained HTML page with the scan summary, bar and
+/// pie charts as inline SVG and a collapsible folder tree. The page has no
+/// scripts or external resources, so it can be attached to a ticket as is.
+pub fn write_html_report(
+    writer: impl Write,
+    info: &ScanInfo,
+    results: &[FolderInfo],
+    chart_items: usize,
+) -> io::Result<()> {
+    let mut out = io::BufWriter::new(writer);
+    let total: u64 = results.iter().map(|folder| folder.size).sum();
+    let files: u64 = results.iter().map(|folder| folder.node.file_count()).sum();
+    let root = info.root.display().to_string();
+
+    writeln!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>Folder sizes: {}</title>\n<style>{}</style>\n</head>\n<body>", escape(&root), STYLE)?;
+    writeln!(out, "<h1>Folder Size Report</h1>\n<table class=\"summary\">")?;
+    summary_row(&mut out, "Root", &root)?;
+    if let Some(started) = info.started {
+        summary_row(&mut out, "Scan started (UTC)", &format_timestamp(started))?;
+    }
+    if let Some(finished) = info.finished {
+        summary_row(&mut out, "Scan finished (UTC)", &format_timestamp(finished))?;
+    }
+    summary_row(&mut out, "Total size", &format!("{} ({} bytes)", format_size(total), total))?;
+    summary_row(&mut out, "Folders", &results.len().to_string())?;
+    summary_row(&mut out, "Files", &files.to_string())?;
+    summary_row(&mut out, "Generated (UTC)", &format_timestamp(std::time::SystemTime::now()))?;
+    writeln!(out, "</table>")?;
+
+    if !info.errors.is_empty() {
+        writeln!(out, "<h2>Errors</h2>\n<ul class=\"errors\">")?;
+        for error in &info.errors {
+            writeln!(out, "<li>{}: {}</li>", escape(&error.path.display().to_string()), escape(&error.message))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+
+    writeln!(out, "<h2>Size Distribution</h2>\n<div class=\"charts\">")?;
+    writeln!(out, "{}", svg_chart::bar_chart_svg(results, chart_items, 640.0, 320.0))?;
+    writeln!(out, "{}", svg_chart::pie_chart_svg(results, chart_items, 640.0, 320.0))?;
+    writeln!(out, "</div>")?;
+
+    writeln!(out, "<h2>Folders</h2>\n<div class=\"tree\">")?;
+    for folder in results {
+        write_tree_node(&mut out, &folder.node, total, 0)?;
+    }
+    writeln!(out, "</div>\n</body>\n</html>")?;
+    out.flush()
+}
+
+fn summary_row(out: &mut impl Write, label: &str, value: &str) -> io::Result<()> {
+    writeln!(out, "<tr><td><strong>{}</strong></td><td>{}</td></tr>", escape(label), escape(value))
+}
+
+// One row: name, size and a bar showing the share of the parent folder
+fn write_row_content(out: &mut impl Write, node: &FsNode, parent_size: u64) -> io::Result<()> {
+    let share = if parent_size > 0 {
+        node.size as f64 / parent_size as f64 * 100.0
+    } else {
+        0.0
+    };
+    write!(
+        out,
+        "<span class=\"name\" title=\"{name}\">{name}</span><span class=\"size\">{size}</span>\
+         <span class=\"bar\" title=\"{share:.1}%\"><span style=\"width:{share:.1}%\"></span></span>",
+        name = escape(&node.name),
+        size = escape(&format_size(node.size)),
+    )
+}
+
+fn write_tree_node(out: &mut impl Write, node: &FsNode, parent_size: u64, depth: usize) -> io::Result<()> {
+    if !node.is_browsable() {
+        write!(out, "<div class=\"file\">")?;
+        write_row_content(out, node, parent_size)?;
+        return writeln!(out, "</div>");
+    }
+    // Folders at the depth limit still open, to say what was left out
+    if depth >= MAX_TREE_DEPTH {
+        write!(out, "<details><summary>")?;
+        write_row_content(out, node, parent_size)?;
+        writeln!(out, "</summary>")?;
+        writeln!(
+            out,
+            "<div class=\"more\">… deeper levels omitted: {} items, {}</div>",
+            node.children.len(),
+            escape(&format_size(node.size))
+        )?;
+        return writeln!(out, "</details>");
+    }
+
+    // Top-level folders start expanded
+    write!(out, "<details{}><summary>", if depth == 0 { " open" } else { "" })?;
+    write_row_content(out, node, parent_size)?;
+    writeln!(out, "</summary>")?;
+
+    let mut children: Vec<&FsNode> = node.children.iter().map(|c| c.as_ref()).collect();
+    children.sort_by_key(|child| std::cmp::Reverse(child.size));
+    for child in children.iter().take(MAX_CHILDREN_PER_FOLDER) {
+        write_tree_node(out, child, node.size, depth + 1)?;
+    }
+    if children.len() > MAX_CHILDREN_PER_FOLDER {
+        let rest = &children[MAX_CHILDREN_PER_FOLDER..];
+        let rest_size: u64 = rest.iter().map(|child| child.size).sum();
+        writeln!(
+            out,
+            "<div class=\"more\">… {} more items, {}</div>",
+            rest.len(),
+            escape(&format_size(rest_size))
+        )?;
+    }
+    writeln!(out, "</details>")
+}
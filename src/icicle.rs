@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use eframe::egui;
+use folder_size_analyzer::svg_chart::palette_color;
+use folder_size_analyzer::{format_size, FolderInfo, FsNode};
+
+use crate::treemap::shade;
+
+const ROW_HEIGHT: f32 = 18.0;
+const MAX_LEVELS: usize = 16;
+// Narrower boxes are skipped along with everything below them
+const MIN_WIDTH: f32 = 0.5;
+
+struct IcicleBox {
+    rect: egui::Rect,
+    // From the top-level entry of the view down to the box's node
+    chain: Vec<Arc<FsNode>>,
+}
+
+/// Draws `rows` as an icicle chart: one row of boxes per directory level,
+/// each box as wide as its share of the view and placed under its parent.
+///
+/// Boxes whose name contains `search` (ignoring case) are highlighted and
+/// the rest dimmed. Returns the chain of nodes down to the clicked box,
+/// outermost first.
+pub fn show(ui: &mut egui::Ui, rows: &[FolderInfo], width: f32, search: &str) -> Option<Vec<Arc<FsNode>>> {
+    let total: u64 = rows.iter().map(|info| info.size).sum();
+    let scale = if total > 0 { width / total as f32 } else { 0.0 };
+    let levels = rows
+        .iter()
+        .map(|info| depth(&info.node, info.size as f32 * scale, MAX_LEVELS))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, levels as f32 * ROW_HEIGHT), egui::Sense::click());
+    if total == 0 {
+        return None;
+    }
+
+    let search = search.trim().to_lowercase();
+    let mut boxes = Vec::new();
+    let mut x = response.rect.left();
+    for (index, info) in rows.iter().enumerate() {
+        let box_width = info.size as f32 * scale;
+        let style = Style {
+            base: palette_color(index),
+            search: &search,
+            background: ui.visuals().extreme_bg_color,
+        };
+        draw_box(&painter, &mut boxes, vec![info.node.clone()], x, box_width, response.rect.top(), &style);
+        x += box_width;
+    }
+
+    let pointer = response.hover_pos()?;
+    let hovered = boxes.iter().find(|b| b.rect.contains(pointer))?;
+    painter.rect_stroke(hovered.rect, 0.0, egui::Stroke::new(2.0, egui::Color32::WHITE));
+
+    let node = hovered.chain.last()?;
+    let path: Vec<&str> = hovered.chain.iter().map(|node| node.name.as_str()).collect();
+    let response = response.on_hover_ui_at_pointer(|ui| {
+        ui.strong(path.join("/"));
+        ui.label(format!(
+            "{} ({:.1}% of view)",
+            format_size(node.size),
+            node.size as f64 / total as f64 * 100.0
+        ));
+    });
+    response.clicked().then(|| hovered.chain.clone())
+}
+
+struct Style<'a> {
+    base: [u8; 3],
+    search: &'a str,
+    background: egui::Color32,
+}
+
+fn draw_box(
+    painter: &egui::Painter,
+    boxes: &mut Vec<IcicleBox>,
+    chain: Vec<Arc<FsNode>>,
+    x: f32,
+    width: f32,
+    top: f32,
+    style: &Style,
+) {
+    let level = chain.len() - 1;
+    if width < MIN_WIDTH || level >= MAX_LEVELS {
+        return;
+    }
+    let node = chain.last().unwrap().clone();
+    let rect = egui::Rect::from_min_size(
+        egui::pos2(x, top + level as f32 * ROW_HEIGHT),
+        egui::vec2(width, ROW_HEIGHT),
+    );
+
+    let matches = !style.search.is_empty() && node.name.to_lowercase().contains(style.search);
+    let mut color = shade(style.base, level);
+    if !style.search.is_empty() && !matches {
+        color = color.gamma_multiply(0.3);
+    }
+    painter.rect_filled(rect, 0.0, color);
+    painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, style.background));
+    if matches {
+        painter.rect_stroke(rect.shrink(1.0), 0.0, egui::Stroke::new(2.0, egui::Color32::YELLOW));
+    }
+    if width > 30.0 {
+        painter.with_clip_rect(rect.shrink(1.0)).text(
+            rect.left_center() + egui::vec2(3.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            &node.name,
+            egui::FontId::proportional(11.0),
+            egui::Color32::WHITE,
+        );
+    }
+    boxes.push(IcicleBox {
+        rect,
+        chain: chain.clone(),
+    });
+
+    if node.size == 0 {
+        return;
+    }
+    let mut children: Vec<&Arc<FsNode>> = node.children.iter().collect();
+    children.sort_by_key(|child| std::cmp::Reverse(child.size));
+    let mut child_x = x;
+    for child in children {
+        let child_width = child.size as f32 / node.size as f32 * width;
+        let mut child_chain = chain.clone();
+        child_chain.push(child.clone());
+        draw_box(painter, boxes, child_chain, child_x, child_width, top, style);
+        child_x += child_width;
+    }
+}
+
+// Number of levels that will be drawn for `node` at `width`, up to `limit`.
+// Only boxes wide enough to draw are visited, which keeps huge trees cheap.
+fn depth(node: &FsNode, width: f32, limit: usize) -> usize {
+    if width < MIN_WIDTH || limit == 0 {
+        return 0;
+    }
+    if node.size == 0 {
+        return 1;
+    }
+    1 + node
+        .children
+        .iter()
+        .map(|child| depth(child, child.size as f32 / node.size as f32 * width, limit - 1))
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,88 @@
+//! Folder size scanning engine behind the Folder Size Analyzer window and
+//! command line.
+//!
+//! A scan lists the folders directly below a root directory, reads each of
+//! them into an [`FsNode`] tree in parallel and returns them sorted by size:
+//!
+//! ```no_run
+//! use folder_size_analyzer::{format_size, Scanner};
+//!
+//! let report = Scanner::new("/home").look_inside_archives(true).scan()?;
+//! for folder in report.folders.iter().take(10) {
+//!     println!("{:>12}  {}", format_size(folder.size), folder.path.display());
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! The results can then be written with the exporters ([`export`], [`ncdu`],
+//! [`html_report`], [`chart_export`]), searched by name or [`filter`]
+//! expression ([`search`]) or analysed further ([`duplicates`], [`cleanup`],
+//! [`sparse`]).
+
+#![warn(missing_docs)]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Lists the contents of zip and tar archives as virtual folders.
+pub mod archive;
+/// Bar, pie and sunburst charts saved as SVG or PNG.
+pub mod chart_export;
+/// Empty folders and zero-byte files.
+pub mod cleanup;
+/// Replaces duplicate files with hard links or reflink clones.
+pub mod dedupe;
+/// Finds files with identical content.
+pub mod duplicates;
+/// JSON, CSV and folded-stack exports, and reading JSON exports back.
+pub mod export;
+/// Filter expressions over size, age, type and names.
+pub mod filter;
+/// Self-contained HTML report of a scan.
+pub mod html_report;
+/// Results read from `find` or `du` output.
+pub mod listing;
+/// Reading and writing ncdu's JSON dump format.
+pub mod ncdu;
+/// Who owns a file.
+pub mod owner;
+/// The scan itself and the events it reports.
+pub mod scanner;
+/// Searching the scanned tree by name or filter expression.
+pub mod search;
+/// Sparse, compressed and preallocated files.
+pub mod sparse;
+/// Charts drawn as SVG, and the colors shared with the window.
+pub mod svg_chart;
+/// The scanned tree and what is known about a scan.
+pub mod tree;
+
+pub use scanner::{ScanEvent, ScanReport, Scanner};
+pub use tree::{FsNode, ScanError, ScanInfo, ScanOptions};
+
+/// One top-level folder of a scan.
+#[derive(Debug, Clone)]
+pub struct FolderInfo {
+    /// Full path of the folder: the scanned root joined with its name.
+    pub path: PathBuf,
+    /// Total size of all files below the folder, same as `node.size`.
+    pub size: u64,
+    /// Everything below the folder. Shared, so results are cheap to clone.
+    pub node: Arc<FsNode>,
+}
+
+/// Formats a byte count with decimal units, e.g. `1.50 MB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", size, UNITS[unit])
+    }
+}
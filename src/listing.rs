@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::tree::FsNode;
+use crate::FolderInfo;
+
+// How many malformed lines are kept for the error message
+const MAX_REPORTED_LINES: usize = 5;
+
+/// Scan results read by [`read_listing`].
+pub struct Listing {
+    /// The directory the folders are in.
+    pub root: PathBuf,
+    /// The folders directly below the root, largest first.
+    pub folders: Vec<FolderInfo>,
+    /// Lines that were skipped because they are not `<bytes> <path>`.
+    pub malformed_count: usize,
+    /// The first few malformed lines with their 1-based line numbers.
+    pub malformed: Vec<(usize, String)>,
+}
+
+#[derive(Default)]
+struct Builder {
+    size: u64,
+    is_dir: bool,
+    children: BTreeMap<String, Builder>,
+}
+
+impl Builder {
+    fn into_node(self, name: String) -> FsNode {
+        if !self.is_dir {
+            return FsNode {
+                name,
+                size: self.size,
+                allocated: self.size,
+                ..Default::default()
+            };
+        }
+        // A directory's own listed size (du's cumulative total, or the inode
+        // size from find) is ignored in favour of the sum of what is below it.
+        let children: Vec<Arc<FsNode>> = self
+            .children
+            .into_iter()
+            .map(|(name, child)| Arc::new(child.into_node(name)))
+            .collect();
+        FsNode {
+            name,
+            size: children.iter().map(|c| c.size).sum(),
+            allocated: children.iter().map(|c| c.allocated).sum(),
+            is_dir: true,
+            children,
+            ..Default::default()
+        }
+    }
+}
+
+/// Builds scan results from a text listing with one `<bytes> <path>` entry
+/// per line, as written by `find -printf '%s %p\n'` or `du -ab`. Size and
+/// path may be separated by spaces or a tab; the path is the rest of the line.
+///
+/// An entry whose path is a prefix of another entry's is a folder, as is one
+/// written with a trailing `/`; everything else is a file. That makes an
+/// empty folder in plain `find` or `du` output, such as `4096 ./a`, a
+/// 4096-byte file; `find . -type d -printf '%s %p/\n' -o -printf '%s %p\n'`
+/// marks folders so they are kept even when empty. The root is the
+/// deepest directory shared by all entries, or its parent when it only
+/// holds files. Like a live scan, only the folders directly below it become
+/// results, and a listing without any is an error.
+pub fn read_listing(reader: impl BufRead) -> io::Result<Listing> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut malformed_count = 0;
+    let mut malformed = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line(&line) {
+            Some(entry) => entries.push(entry),
+            None => {
+                malformed_count += 1;
+                if malformed.len() < MAX_REPORTED_LINES {
+                    malformed.push((index + 1, line));
+                }
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("listing contains no valid entries ({} malformed lines)", malformed_count),
+        ));
+    }
+
+    let mut root_len = common_prefix_len(&entries);
+    // With a single file the shared prefix is the file itself
+    if entries.iter().all(|entry| entry.components.len() == root_len) {
+        root_len = root_len.saturating_sub(1);
+    }
+    // A listing of one folder's files shows that folder, from its parent
+    let has_folders = |root_len: usize| {
+        entries.iter().any(|entry| {
+            let depth = entry.components.len();
+            depth > root_len + 1 || (entry.is_dir && depth == root_len + 1)
+        })
+    };
+    if !has_folders(root_len) && root_len > 0 {
+        root_len -= 1;
+    }
+    let mut root: PathBuf = entries[0].components[..root_len].iter().collect();
+    if root.as_os_str().is_empty() {
+        root = PathBuf::from(".");
+    }
+
+    let mut tree = Builder::default();
+    for entry in entries {
+        let mut node = &mut tree;
+        for component in &entry.components[root_len..] {
+            // Everything on the way to an entry is a folder
+            node.is_dir = true;
+            node = node.children.entry(component.clone()).or_default();
+        }
+        node.size = entry.size;
+        node.is_dir |= entry.is_dir;
+    }
+
+    let mut folders: Vec<FolderInfo> = tree
+        .children
+        .into_iter()
+        .filter(|(_, child)| child.is_dir)
+        .map(|(name, child)| {
+            let node = child.into_node(name);
+            FolderInfo {
+                path: root.join(&node.name),
+                size: node.size,
+                node: Arc::new(node),
+            }
+        })
+        .collect();
+    folders.sort_by_key(|info| std::cmp::Reverse(info.size));
+    if folders.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("listing has only files directly in {}, no folders", root.display()),
+        ));
+    }
+
+    Ok(Listing {
+        root,
+        folders,
+        malformed_count,
+        malformed,
+    })
+}
+
+struct Entry {
+    components: Vec<String>,
+    size: u64,
+    // Written with a trailing slash
+    is_dir: bool,
+}
+
+fn parse_line(line: &str) -> Option<Entry> {
+    let line = line.trim_start();
+    let split = line.find(|c: char| c.is_whitespace())?;
+    let size = line[..split].parse().ok()?;
+    let path = line[split..].trim_start_matches([' ', '\t']);
+    if path.is_empty() {
+        return None;
+    }
+
+    // "./a/b" and "a/b" name the same entry, and "." is the listing root
+    let components = Path::new(path)
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(Entry {
+        components,
+        size,
+        is_dir: path.ends_with('/'),
+    })
+}
+
+fn common_prefix_len(entries: &[Entry]) -> usize {
+    let first = &entries[0].components;
+    entries[1..].iter().fold(first.len(), |len, entry| {
+        first[..len]
+            .iter()
+            .zip(&entry.components)
+            .take_while(|(a, b)| a == b)
+            .count()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> Listing {
+        read_listing(text.as_bytes()).unwrap()
+    }
+
+    fn names(listing: &Listing) -> Vec<String> {
+        let mut names: Vec<_> = listing.folders.iter().map(|f| f.node.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn reads_find_output() {
+        let listing = read(".\t4096\n4096 ./a\n10 ./a/x\n4096 ./b\n4096 ./b/c\n5 ./b/c/y\n");
+        assert_eq!(listing.root, PathBuf::from("."));
+        assert_eq!(names(&listing), ["a", "b"]);
+        // The malformed first line has the size and path swapped
+        assert_eq!(listing.malformed, [(1, ".\t4096".to_string())]);
+        assert_eq!(listing.folders[0].path, PathBuf::from("./a"));
+        assert_eq!(listing.folders[0].size, 10);
+        assert_eq!(listing.folders[1].size, 5);
+    }
+
+    #[test]
+    fn reads_du_output_ignoring_folder_totals() {
+        let listing = read("10\t./a/x\n4106\t./a\n5\t./b/y\n4101\t./b\n8207\t.\n");
+        assert_eq!(names(&listing), ["a", "b"]);
+        assert_eq!(listing.folders[0].size, 10);
+        assert!(!listing.folders[0].node.children[0].is_dir);
+    }
+
+    #[test]
+    fn roots_absolute_paths_at_their_shared_folder() {
+        let listing = read("3 /home/u/a/x\n4 /home/u/b/y\n");
+        assert_eq!(listing.root, PathBuf::from("/home/u"));
+        assert_eq!(listing.folders[0].path, PathBuf::from("/home/u/b"));
+    }
+
+    #[test]
+    fn shows_a_single_file_from_its_folder() {
+        let listing = read("7 /data/logs/app.log\n");
+        assert_eq!(listing.root, PathBuf::from("/data"));
+        assert_eq!(names(&listing), ["logs"]);
+        assert_eq!(listing.folders[0].size, 7);
+    }
+
+    #[test]
+    fn empty_folders_need_a_trailing_slash() {
+        // Without the slash `./a` can't be told apart from a file
+        let listing = read("4096 ./a\n4096 ./b\n3 ./b/f\n");
+        assert_eq!(names(&listing), ["b"]);
+
+        let listing = read("4096 ./a/\n4096 ./b/\n3 ./b/f\n");
+        assert_eq!(names(&listing), ["a", "b"]);
+        assert_eq!(listing.folders[1].size, 0);
+        assert!(listing.folders[1].node.children.is_empty());
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        let text = "x ./a\n12\n-1 ./b\n3 ./c/f\n\n1.5 ./d\nsize ./e\nbad ./f\n";
+        let listing = read(text);
+        assert_eq!(names(&listing), ["c"]);
+        assert_eq!(listing.malformed_count, 6);
+        assert_eq!(listing.malformed.len(), MAX_REPORTED_LINES);
+        assert_eq!(listing.malformed[1], (2, "12".to_string()));
+        assert_eq!(listing.malformed[4], (7, "size ./e".to_string()));
+
+        let error = read_listing("junk\nmore junk\n".as_bytes()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "listing contains no valid entries (2 malformed lines)");
+    }
+
+    #[test]
+    fn refuses_listings_without_folders() {
+        let error = read_listing("1 ./x\n2 ./y\n".as_bytes()).err().unwrap();
+        assert_eq!(error.to_string(), "listing has only files directly in ., no folders");
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+use crate::export;
+use crate::tree::FsNode;
+use crate::FolderInfo;
+
+// ncdu export format version written by `ncdu -o`
+const MAJOR_VERSION: u64 = 1;
+const MINOR_VERSION: u64 = 2;
+
+/// Reads an `ncdu -o` JSON dump.
+///
+/// Returns the scanned root path and its subfolders in the same shape as a
+/// live scan: one entry per top-level folder, sorted by size.
+pub fn read_export(reader: impl Read) -> io::Result<(PathBuf, Vec<FolderInfo>)> {
+    // Refuses dumps nested too deep for the recursive walk below
+    let value: Value = export::from_nested_json(reader)?;
+    let Some(items) = value.as_array() else {
+        return Err(invalid_data("not an ncdu export: expected a JSON array"));
+    };
+    if items.first().and_then(Value::as_u64) != Some(MAJOR_VERSION) {
+        return Err(invalid_data("unsupported ncdu export version"));
+    }
+    let root = items
+        .get(3)
+        .ok_or_else(|| invalid_data("ncdu export has no directory tree"))?;
+
+    let root_node = parse_entry(root)?;
+    if !root_node.is_dir {
+        return Err(invalid_data("ncdu export root is not a directory"));
+    }
+    // ncdu stores the full scanned path as the root's name
+    let root_path = PathBuf::from(&root_node.name);
+
+    let mut folders: Vec<FolderInfo> = root_node
+        .children
+        .iter()
+        .filter(|child| child.is_dir)
+        .map(|child| FolderInfo {
+            path: root_path.join(&child.name),
+            size: child.size,
+            node: child.clone(),
+        })
+        .collect();
+    folders.sort_by_key(|info| std::cmp::Reverse(info.size));
+    Ok((root_path, folders))
+}
+
+// Directories are arrays of [info, child...], files are bare info objects
+fn parse_entry(value: &Value) -> io::Result<FsNode> {
+    match value {
+        Value::Array(items) => {
+            let info = items
+                .first()
+                .ok_or_else(|| invalid_data("empty directory entry"))?;
+            let mut node = parse_info(info)?;
+            node.is_dir = true;
+            // A directory's own sizes are just its inode; the total comes from the children
+            node.size = 0;
+            node.allocated = 0;
+            for child in &items[1..] {
+                let child = parse_entry(child)?;
+                node.size += child.size;
+                node.allocated += child.allocated;
+                node.modified = node.modified.max(child.modified);
+                node.children.push(Arc::new(child));
+            }
+            Ok(node)
+        }
+        Value::Object(_) => parse_info(value),
+        _ => Err(invalid_data("unexpected value in directory tree")),
+    }
+}
+
+fn parse_info(info: &Value) -> io::Result<FsNode> {
+    let name = info
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid_data("entry without a name"))?;
+    let asize = info.get("asize").and_then(Value::as_u64);
+    let dsize = info.get("dsize").and_then(Value::as_u64);
+    // Only present in extended exports (`ncdu -e`)
+    let mtime = info.get("mtime").and_then(Value::as_u64);
+    Ok(FsNode {
+        name: name.to_string(),
+        size: asize.or(dsize).unwrap_or(0),
+        allocated: dsize.or(asize).unwrap_or(0),
+        modified: mtime.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        ..Default::default()
+    })
+}
+
+/// Writes the scan in the format produced by `ncdu -o`, so it can be opened
+/// with `ncdu -f` or imported again. Archive contents are left out since
+/// ncdu has no notion of virtual entries.
+pub fn write_export(writer: impl Write, root: &Path, results: &[FolderInfo]) -> io::Result<()> {
+    let mut out = io::BufWriter::new(writer);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    write!(
+        out,
+        "[{},{},{{\"progname\":\"folder-size-analyzer\",\"progver\":\"{}\",\"timestamp\":{}}},\n[{{\"name\":{}}}",
+        MAJOR_VERSION,
+        MINOR_VERSION,
+        env!("CARGO_PKG_VERSION"),
+        timestamp,
+        json_string(&root.display().to_string())
+    )?;
+    for info in results {
+        out.write_all(b",\n")?;
+        write_node(&mut out, &info.node)?;
+    }
+    out.write_all(b"]\n]\n")?;
+    out.flush()
+}
+
+fn write_node(out: &mut impl Write, node: &FsNode) -> io::Result<()> {
+    let mtime = node
+        .modified
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| format!(",\"mtime\":{}", d.as_secs()))
+        .unwrap_or_default();
+    if node.is_dir {
+        write!(out, "[{{\"name\":{}{}}}", json_string(&node.name), mtime)?;
+        for child in &node.children {
+            out.write_all(b",\n")?;
+            write_node(out, child)?;
+        }
+        out.write_all(b"]")
+    } else {
+        write!(
+            out,
+            "{{\"name\":{},\"asize\":{},\"dsize\":{}{}}}",
+            json_string(&node.name),
+            node.size,
+            node.allocated,
+            mtime
+        )
+    }
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A dump whose single folder has `depth` levels of subfolders
+    fn nested_dump(depth: usize) -> String {
+        let mut tree = String::from(r#"{"name":"leaf","asize":3,"dsize":4096}"#);
+        for level in 0..depth {
+            tree = format!(r#"[{{"name":"d{}"}},{}]"#, level, tree);
+        }
+        format!(r#"[1,2,{{"progname":"ncdu"}},[{{"name":"/r"}},{}]]"#, tree)
+    }
+
+    #[test]
+    fn reads_dumps_deeper_than_the_default_limit() {
+        let (root, folders) = read_export(nested_dump(1000).as_bytes()).unwrap();
+        assert_eq!(root, PathBuf::from("/r"));
+        assert_eq!(folders[0].size, 3);
+        let mut node = folders[0].node.as_ref();
+        let mut depth = 0;
+        while let Some(child) = node.children.first() {
+            node = child;
+            depth += 1;
+        }
+        assert_eq!(depth, 1000);
+    }
+
+    #[test]
+    fn refuses_dumps_nested_too_deep() {
+        let error = read_export(nested_dump(5000).as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "JSON is nested deeper than 1024 levels");
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+
+/// Name of the user who owns `path`, or their numeric id when the id has
+/// no name. `None` when the path can't be read, and always on platforms
+/// other than Unix and Windows.
+#[cfg(unix)]
+pub fn owner(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let uid = std::fs::symlink_metadata(path).ok()?.uid();
+    Some(user_names().get(&uid).cloned().unwrap_or_else(|| uid.to_string()))
+}
+
+#[cfg(windows)]
+pub fn owner(path: &Path) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use winapi::um::accctrl::SE_FILE_OBJECT;
+    use winapi::um::aclapi::GetNamedSecurityInfoW;
+    use winapi::um::winbase::LocalFree;
+    use winapi::um::winnt::{OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut sid: PSID = null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = null_mut();
+    let status = unsafe {
+        GetNamedSecurityInfoW(
+            wide.as_ptr(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            &mut sid,
+            null_mut(),
+            null_mut(),
+            null_mut(),
+            &mut descriptor,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    // The owner points into the descriptor, so it is named before the
+    // descriptor is freed
+    let name = unsafe { account_name(sid) };
+    unsafe { LocalFree(descriptor) };
+    name
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn owner(_path: &Path) -> Option<String> {
+    None
+}
+
+// Read once from /etc/passwd. Users that only exist in a directory service
+// show up as numeric ids.
+#[cfg(unix)]
+fn user_names() -> &'static std::collections::HashMap<u32, String> {
+    static NAMES: std::sync::OnceLock<std::collections::HashMap<u32, String>> = std::sync::OnceLock::new();
+    NAMES.get_or_init(|| {
+        let passwd = std::fs::read_to_string("/etc/passwd").unwrap_or_default();
+        passwd
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let uid = fields.nth(1)?.parse().ok()?;
+                Some((uid, name.to_string()))
+            })
+            .collect()
+    })
+}
+
+// `DOMAIN\user` for an account, or the SID in its `S-1-5-...` form when the
+// account can't be looked up, such as a deleted user or an unreachable domain.
+#[cfg(windows)]
+unsafe fn account_name(sid: winapi::um::winnt::PSID) -> Option<String> {
+    use std::ptr::null;
+    use winapi::um::securitybaseapi::{GetSidIdentifierAuthority, GetSidSubAuthority, GetSidSubAuthorityCount, IsValidSid};
+    use winapi::um::winbase::LookupAccountSidW;
+    use winapi::um::winnt::{SID, SID_NAME_USE};
+
+    if sid.is_null() || IsValidSid(sid) == 0 {
+        return None;
+    }
+    let mut name = [0u16; 256];
+    let mut domain = [0u16; 256];
+    let mut name_len = name.len() as u32;
+    let mut domain_len = domain.len() as u32;
+    let mut kind: SID_NAME_USE = 0;
+    let found = LookupAccountSidW(
+        null(),
+        sid,
+        name.as_mut_ptr(),
+        &mut name_len,
+        domain.as_mut_ptr(),
+        &mut domain_len,
+        &mut kind,
+    );
+    if found != 0 {
+        let name = String::from_utf16_lossy(&name[..name_len as usize]);
+        let domain = String::from_utf16_lossy(&domain[..domain_len as usize]);
+        return Some(if domain.is_empty() { name } else { format!("{}\\{}", domain, name) });
+    }
+
+    let authority = (*GetSidIdentifierAuthority(sid)).Value;
+    let authority = authority.iter().fold(0u64, |value, &byte| (value << 8) | byte as u64);
+    let mut text = format!("S-{}-{}", (*(sid as *const SID)).Revision, authority);
+    for index in 0..*GetSidSubAuthorityCount(sid) {
+        text.push_str(&format!("-{}", *GetSidSubAuthority(sid, index as u32)));
+    }
+    Some(text)
+}
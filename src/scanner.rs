@@ -0,0 +1,236 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rayon::prelude::*;
+
+use crate::tree::{self, ScanError, ScanInfo, ScanOptions};
+use crate::FolderInfo;
+
+/// Builder for a scan of the folders below a root directory.
+///
+/// [`Scanner::scan`] simply blocks until the results are ready. To follow a
+/// scan while it runs, call [`Scanner::scan_with_events`] on a worker thread
+/// and read the [`ScanEvent`]s from the other end of the channel:
+///
+/// ```no_run
+/// use std::sync::mpsc;
+/// use folder_size_analyzer::{ScanEvent, Scanner};
+///
+/// let (sender, events) = mpsc::channel();
+/// let scanner = Scanner::new(".");
+/// std::thread::spawn(move || scanner.scan_with_events(sender));
+/// for event in events {
+///     match event {
+///         ScanEvent::FolderCompleted(folder) => println!("{}", folder.path.display()),
+///         ScanEvent::Finished(report) => println!("{} bytes", report.total_size()),
+///         _ => {}
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    root: PathBuf,
+    options: ScanOptions,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// The folders found by a scan, largest first, and how the scan went.
+#[derive(Debug, Clone)]
+pub struct ScanReport {
+    /// The folders directly below the root, largest first.
+    pub folders: Vec<FolderInfo>,
+    /// The root, timing, options and errors of the scan.
+    pub info: ScanInfo,
+}
+
+/// Something that happened during a scan, sent in the order it happened.
+///
+/// Every scan ends with exactly one `Finished`, `Cancelled` or `Failed`.
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    /// The root was listed and `folders` subfolders are about to be scanned.
+    Started {
+        /// The directory being scanned.
+        root: PathBuf,
+        /// Number of folders directly below the root.
+        folders: usize,
+    },
+    /// A directory at any depth is about to be read.
+    DirectoryEntered(PathBuf),
+    /// A top-level folder has been scanned completely.
+    FolderCompleted(FolderInfo),
+    /// A top-level folder could not be read and is left out of the results.
+    Error(ScanError),
+    /// All folders have been scanned; the report has the complete results.
+    Finished(ScanReport),
+    /// The scan was stopped through the flag given to [`Scanner::cancel_flag`].
+    Cancelled,
+    /// The root itself could not be read.
+    Failed(ScanError),
+}
+
+// Where the scan reports to; a blocking scan has nobody listening
+pub(crate) struct EventSink {
+    sender: Option<Sender<ScanEvent>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl EventSink {
+    pub(crate) fn send(&self, event: ScanEvent) {
+        if let Some(sender) = &self.sender {
+            // Nobody listening any more is not a reason to stop the scan
+            let _ = sender.send(event);
+        }
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Scanner {
+    /// A scan of the folders directly below `root`, with default options.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            options: ScanOptions::default(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Replaces all scan options at once.
+    pub fn options(mut self, options: ScanOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// List the entries of zip and tar archives as virtual sub-folders.
+    pub fn look_inside_archives(mut self, enabled: bool) -> Self {
+        self.options.look_inside_archives = enabled;
+        self
+    }
+
+    /// Setting the flag from another thread stops the scan at the next
+    /// directory.
+    pub fn cancel_flag(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = cancelled;
+        self
+    }
+
+    /// Runs the scan on the rayon pool and blocks until it is done.
+    ///
+    /// Folders that can't be read are listed in [`ScanInfo::errors`]; only
+    /// failing to list the root itself is an error. A cancelled scan fails
+    /// with [`io::ErrorKind::Interrupted`].
+    pub fn scan(&self) -> io::Result<ScanReport> {
+        self.run(&EventSink {
+            sender: None,
+            cancelled: self.cancelled.clone(),
+        })
+    }
+
+    /// Like [`Scanner::scan`], but reports every step to `events` and
+    /// delivers the report in the final [`ScanEvent::Finished`].
+    pub fn scan_with_events(&self, events: Sender<ScanEvent>) {
+        let sink = EventSink {
+            sender: Some(events),
+            cancelled: self.cancelled.clone(),
+        };
+        let last = match self.run(&sink) {
+            Ok(report) => ScanEvent::Finished(report),
+            Err(_) if sink.is_cancelled() => ScanEvent::Cancelled,
+            Err(e) => ScanEvent::Failed(ScanError {
+                path: self.root.clone(),
+                message: e.to_string(),
+            }),
+        };
+        sink.send(last);
+    }
+
+    fn run(&self, sink: &EventSink) -> io::Result<ScanReport> {
+        let started = SystemTime::now();
+        let folders: Vec<PathBuf> = fs::read_dir(&self.root)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+            .map(|entry| entry.path())
+            .collect();
+        sink.send(ScanEvent::Started {
+            root: self.root.clone(),
+            folders: folders.len(),
+        });
+
+        let scanned: Vec<Result<FolderInfo, ScanError>> = folders
+            .par_iter()
+            .map(|path| scan_folder(path, &self.options, sink))
+            .collect();
+        if sink.is_cancelled() {
+            return Err(cancelled());
+        }
+
+        let mut successful = Vec::new();
+        let mut errors = Vec::new();
+        for folder in scanned {
+            match folder {
+                Ok(info) => successful.push(info),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        // Sort descending by size
+        successful.sort_by_key(|info| Reverse(info.size));
+
+        let info = ScanInfo {
+            root: self.root.clone(),
+            started: Some(started),
+            finished: Some(SystemTime::now()),
+            options: self.options.clone(),
+            errors,
+        };
+        Ok(ScanReport {
+            folders: successful,
+            info,
+        })
+    }
+}
+
+fn scan_folder(path: &Path, options: &ScanOptions, sink: &EventSink) -> Result<FolderInfo, ScanError> {
+    match tree::scan_dir_tree(path, options, sink) {
+        Ok(node) => {
+            let info = FolderInfo {
+                path: path.to_path_buf(),
+                size: node.size,
+                node: Arc::new(node),
+            };
+            sink.send(ScanEvent::FolderCompleted(info.clone()));
+            Ok(info)
+        }
+        Err(e) => {
+            let error = ScanError {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            };
+            // Folders cut short by a cancel are not worth reporting
+            if !sink.is_cancelled() {
+                sink.send(ScanEvent::Error(error.clone()));
+            }
+            Err(error)
+        }
+    }
+}
+
+pub(crate) fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "scan cancelled")
+}
+
+impl ScanReport {
+    /// Sum of the sizes of all folders found.
+    pub fn total_size(&self) -> u64 {
+        self.folders.iter().map(|folder| folder.size).sum()
+    }
+}
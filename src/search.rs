@@ -0,0 +1,119 @@
+use std::fmt;
+
+use crate::filter::Filter;
+use crate::FolderInfo;
+
+/// How a search pattern is matched against entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// The name contains the pattern.
+    Substring,
+    /// Shell wildcards: `*`, `?`, `[abc]` and `{a,b}`.
+    Glob,
+    /// A regular expression.
+    Regex,
+    /// A [`Filter`] expression over size, age, type and names.
+    Expression,
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SearchMode::Substring => "Contains",
+            SearchMode::Glob => "Glob",
+            SearchMode::Regex => "Regex",
+            SearchMode::Expression => "Expression",
+        })
+    }
+}
+
+/// A compiled search pattern. Names are compared ignoring case, and a glob
+/// or regex has to match the whole name.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// The lowercased text to look for.
+    Substring(String),
+    /// A compiled [`SearchMode::Glob`] pattern.
+    Glob(globset::GlobMatcher),
+    /// A compiled [`SearchMode::Regex`] pattern, anchored at both ends.
+    Regex(regex::Regex),
+    /// A parsed [`SearchMode::Expression`].
+    Expression(Filter),
+}
+
+impl Matcher {
+    /// Compiles `pattern`, or describes why it is not valid.
+    pub fn new(pattern: &str, mode: SearchMode) -> Result<Matcher, String> {
+        match mode {
+            SearchMode::Substring => Ok(Matcher::Substring(pattern.to_lowercase())),
+            SearchMode::Glob => globset::GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(|glob| Matcher::Glob(glob.compile_matcher()))
+                .map_err(|e| e.kind().to_string()),
+            SearchMode::Regex => regex::RegexBuilder::new(&format!("^(?:{})$", pattern))
+                .case_insensitive(true)
+                .build()
+                .map(Matcher::Regex)
+                // Syntax errors draw a caret under the pattern; the last
+                // line has the actual message
+                .map_err(|e| match e {
+                    regex::Error::Syntax(text) => {
+                        let message = text.lines().last().unwrap_or_default();
+                        message.trim_start_matches("error: ").to_string()
+                    }
+                    other => other.to_string(),
+                }),
+            SearchMode::Expression => Filter::parse(pattern).map(Matcher::Expression),
+        }
+    }
+
+    /// Whether `entry` matches. Only its name is looked at, apart from
+    /// expressions.
+    pub fn is_match(&self, entry: &FolderInfo) -> bool {
+        let name = &entry.node.name;
+        match self {
+            Matcher::Substring(text) => name.to_lowercase().contains(text.as_str()),
+            Matcher::Glob(glob) => glob.is_match(name),
+            Matcher::Regex(regex) => regex.is_match(name),
+            Matcher::Expression(filter) => filter.matches(entry),
+        }
+    }
+}
+
+/// What [`search`] found.
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults {
+    /// Matching files and folders at any depth, largest first. Entries
+    /// inside opened archives are included with their virtual paths.
+    pub hits: Vec<FolderInfo>,
+    /// Number of matches before `hits` was cut to the limit.
+    pub total: usize,
+}
+
+/// Finds every entry below the results that matches, keeping the `limit`
+/// largest.
+pub fn search(results: &[FolderInfo], matcher: &Matcher, limit: usize) -> SearchResults {
+    let mut hits = Vec::new();
+    for info in results {
+        collect_matches(&mut hits, info, matcher);
+    }
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.size));
+    let total = hits.len();
+    hits.truncate(limit);
+    SearchResults { hits, total }
+}
+
+fn collect_matches(hits: &mut Vec<FolderInfo>, info: &FolderInfo, matcher: &Matcher) {
+    if matcher.is_match(info) {
+        hits.push(info.clone());
+    }
+    for child in &info.node.children {
+        let child_info = FolderInfo {
+            path: info.path.join(&child.name),
+            size: child.size,
+            node: child.clone(),
+        };
+        collect_matches(hits, &child_info, matcher);
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use crate::FolderInfo;
+
+// Differences below this are just block rounding and not worth listing
+const MIN_DIFFERENCE_BYTES: u64 = 1024 * 1024;
+
+/// Which way a file's allocated size differs from its length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationKind {
+    /// Less space on disk than the file length: holes or filesystem compression.
+    Sparse,
+    /// More space reserved on disk than the file length.
+    Preallocated,
+}
+
+impl AllocationKind {
+    /// Name to show in a list of files.
+    pub fn label(self) -> &'static str {
+        match self {
+            AllocationKind::Sparse => "Sparse / compressed",
+            AllocationKind::Preallocated => "Preallocated",
+        }
+    }
+}
+
+/// A file whose allocated size differs noticeably from its length.
+#[derive(Debug, Clone)]
+pub struct AllocationEntry {
+    /// The file.
+    pub path: PathBuf,
+    /// The file length.
+    pub apparent: u64,
+    /// Bytes the file takes on disk.
+    pub allocated: u64,
+    /// Whether the file takes less or more than its length.
+    pub kind: AllocationKind,
+}
+
+impl AllocationEntry {
+    /// Bytes between the length and the space on disk, either way.
+    pub fn difference(&self) -> u64 {
+        self.apparent.abs_diff(self.allocated)
+    }
+}
+
+/// Lists files whose apparent length and allocated size differ noticeably,
+/// largest difference first.
+pub fn find_allocation_mismatches(results: &[FolderInfo]) -> Vec<AllocationEntry> {
+    let mut entries = Vec::new();
+    for info in results {
+        let parent = info.path.parent().unwrap_or(Path::new(""));
+        info.node.visit_files(parent, &mut |path, node| {
+            if node.size.abs_diff(node.allocated) < MIN_DIFFERENCE_BYTES {
+                return;
+            }
+            let kind = if node.allocated < node.size {
+                AllocationKind::Sparse
+            } else {
+                AllocationKind::Preallocated
+            };
+            entries.push(AllocationEntry {
+                path,
+                apparent: node.size,
+                allocated: node.allocated,
+                kind,
+            });
+        });
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.difference()));
+    entries
+}
@@ -0,0 +1,229 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::Arc;
+
+use eframe::egui;
+use folder_size_analyzer::svg_chart;
+use folder_size_analyzer::{format_size, FolderInfo, FsNode};
+
+// Segments narrower than this are not drawn and their children skipped
+const MIN_SWEEP: f32 = 0.004;
+// Arcs are filled as convex pieces at most this wide
+const STEP: f32 = 0.05;
+
+struct Segment {
+    // Index of the row the segment belongs to
+    row: usize,
+    ring: usize,
+    start: f32,
+    end: f32,
+    // From the top-level entry of the view down to the segment's node
+    chain: Vec<Arc<FsNode>>,
+}
+
+/// What the pointer did with the chart this frame. Slices are indexed like
+/// the rows, with `colors.len()` standing for the "Other" slice.
+#[derive(Default)]
+pub struct SunburstResponse {
+    pub hovered: Option<usize>,
+    pub clicked: Option<usize>,
+    /// Chain of nodes down to a double-clicked segment, outermost first
+    pub zoom: Option<Vec<Arc<FsNode>>>,
+}
+
+/// Draws `rows` as a sunburst: the innermost ring is a pie of the rows and
+/// every further ring splits each segment into its children, one
+/// directory level per ring.
+///
+/// `colors` gives the color of each of the first rows; the rest are grouped
+/// into one "Other" slice. Deeper rings use lighter tints. The `highlight`
+/// slice is drawn as if hovered and the `selected` one outlined.
+pub fn show(
+    ui: &mut egui::Ui,
+    rows: &[FolderInfo],
+    size: egui::Vec2,
+    rings: usize,
+    colors: &[egui::Color32],
+    highlight: Option<usize>,
+    selected: Option<usize>,
+) -> SunburstResponse {
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::click());
+    let total: u64 = rows.iter().map(|info| info.size).sum();
+    if total == 0 {
+        return SunburstResponse::default();
+    }
+
+    let center = response.rect.center();
+    let radius = (size.x.min(size.y) * 0.48).min(220.0);
+    let ring_width = radius / rings.max(1) as f32;
+
+    // Start at twelve o'clock and go clockwise, like the exported pie
+    let mut segments = Vec::new();
+    let mut angle = -FRAC_PI_2;
+    for (i, info) in rows.iter().take(colors.len()).enumerate() {
+        let sweep = info.size as f32 / total as f32 * TAU;
+        add_segments(&mut segments, i, vec![info.node.clone()], angle, sweep, rings);
+        angle += sweep;
+    }
+    let other = colors.len();
+    let other_size: u64 = rows.iter().skip(other).map(|info| info.size).sum();
+    if other_size > 0 {
+        segments.push(Segment {
+            row: other,
+            ring: 0,
+            start: angle,
+            end: angle + other_size as f32 / total as f32 * TAU,
+            chain: Vec::new(),
+        });
+    }
+
+    let hovered = response.hover_pos().and_then(|pointer| {
+        let offset = pointer - center;
+        let ring = (offset.length() / ring_width) as usize;
+        let mut pointer_angle = offset.y.atan2(offset.x);
+        if pointer_angle < -FRAC_PI_2 {
+            pointer_angle += TAU;
+        }
+        segments
+            .iter()
+            .find(|s| s.ring == ring && s.start <= pointer_angle && pointer_angle < s.end)
+    });
+    // Everything outside the highlighted slice fades a little
+    let focus = hovered.map(|segment| segment.row).or(highlight);
+
+    let background = ui.visuals().extreme_bg_color;
+    for segment in &segments {
+        let base = if segment.row == other { other_color() } else { colors[segment.row] };
+        let [r, g, b] = svg_chart::tint([base.r(), base.g(), base.b()], segment.ring);
+        let mut color = egui::Color32::from_rgb(r, g, b);
+        if focus.is_some_and(|row| row != segment.row) {
+            color = color.gamma_multiply(0.45);
+        }
+        fill_arc(&painter, center, ring_width, segment, color);
+    }
+    for segment in &segments {
+        // Separators between neighbouring segments
+        for edge in [segment.start, segment.end] {
+            let direction = egui::vec2(edge.cos(), edge.sin());
+            painter.line_segment(
+                [
+                    center + direction * ring_width * segment.ring as f32,
+                    center + direction * ring_width * (segment.ring + 1) as f32,
+                ],
+                egui::Stroke::new(1.0, background),
+            );
+        }
+    }
+    if let Some(segment) = segments.iter().find(|s| s.ring == 0 && Some(s.row) == selected) {
+        outline_arc(&painter, center, ring_width, segment, ui.visuals().selection.stroke);
+    }
+    let Some(hovered) = hovered else {
+        return SunburstResponse::default();
+    };
+    outline_arc(&painter, center, ring_width, hovered, egui::Stroke::new(2.0, egui::Color32::WHITE));
+
+    let (name, size) = match hovered.chain.last() {
+        Some(node) => {
+            let path: Vec<&str> = hovered.chain.iter().map(|node| node.name.as_str()).collect();
+            (path.join("/"), node.size)
+        }
+        None => (format!("Other ({} more)", rows.len() - other), other_size),
+    };
+    let response = response.on_hover_ui_at_pointer(|ui| {
+        ui.strong(name);
+        ui.label(format!(
+            "{} ({:.1}% of view)",
+            format_size(size),
+            size as f64 / total as f64 * 100.0
+        ));
+        if hovered.chain.last().is_some_and(|node| node.is_browsable()) {
+            ui.weak("Double-click to zoom in");
+        }
+    });
+
+    SunburstResponse {
+        hovered: Some(hovered.row),
+        clicked: response.clicked().then_some(hovered.row),
+        zoom: (response.double_clicked() && !hovered.chain.is_empty()).then(|| hovered.chain.clone()),
+    }
+}
+
+// The ring of a segment is its depth in `chain`
+fn add_segments(
+    segments: &mut Vec<Segment>,
+    row: usize,
+    chain: Vec<Arc<FsNode>>,
+    start: f32,
+    sweep: f32,
+    rings: usize,
+) {
+    let ring = chain.len() - 1;
+    if sweep < MIN_SWEEP || ring >= rings {
+        return;
+    }
+    let node = chain.last().unwrap().clone();
+    segments.push(Segment {
+        row,
+        ring,
+        start,
+        end: start + sweep,
+        chain: chain.clone(),
+    });
+    if node.size == 0 {
+        return;
+    }
+
+    let mut children: Vec<&Arc<FsNode>> = node.children.iter().collect();
+    children.sort_by_key(|child| std::cmp::Reverse(child.size));
+    let mut angle = start;
+    for child in children {
+        let child_sweep = child.size as f32 / node.size as f32 * sweep;
+        let mut child_chain = chain.clone();
+        child_chain.push(child.clone());
+        add_segments(segments, row, child_chain, angle, child_sweep, rings);
+        angle += child_sweep;
+    }
+}
+
+// Fills the ring sector in small convex pieces, since egui only fills
+// convex shapes correctly
+fn fill_arc(painter: &egui::Painter, center: egui::Pos2, ring_width: f32, segment: &Segment, color: egui::Color32) {
+    let inner = ring_width * segment.ring as f32;
+    let outer = inner + ring_width;
+    let pieces = ((segment.end - segment.start) / STEP).ceil().max(1.0) as usize;
+    let at = |angle: f32, r: f32| center + r * egui::vec2(angle.cos(), angle.sin());
+    for piece in 0..pieces {
+        let a0 = segment.start + (segment.end - segment.start) * piece as f32 / pieces as f32;
+        let a1 = segment.start + (segment.end - segment.start) * (piece + 1) as f32 / pieces as f32;
+        let points = if inner == 0.0 {
+            vec![center, at(a0, outer), at(a1, outer)]
+        } else {
+            vec![at(a0, inner), at(a0, outer), at(a1, outer), at(a1, inner)]
+        };
+        painter.add(egui::Shape::convex_polygon(points, color, egui::Stroke::NONE));
+    }
+}
+
+// Traces the border of a ring sector
+fn outline_arc(painter: &egui::Painter, center: egui::Pos2, ring_width: f32, segment: &Segment, stroke: egui::Stroke) {
+    let inner = ring_width * segment.ring as f32;
+    let outer = inner + ring_width;
+    let pieces = ((segment.end - segment.start) / STEP).ceil().max(1.0) as usize;
+    let at = |angle: f32, r: f32| center + r * egui::vec2(angle.cos(), angle.sin());
+    let angles: Vec<f32> = (0..=pieces)
+        .map(|piece| segment.start + (segment.end - segment.start) * piece as f32 / pieces as f32)
+        .collect();
+    let mut points: Vec<egui::Pos2> = angles.iter().map(|&angle| at(angle, outer)).collect();
+    if inner == 0.0 {
+        points.push(center);
+    } else {
+        points.extend(angles.iter().rev().map(|&angle| at(angle, inner)));
+    }
+    painter.add(egui::Shape::closed_line(points, stroke));
+}
+
+/// Color of the slice that groups the rows without a color of their own,
+/// the same as in exported charts.
+pub fn other_color() -> egui::Color32 {
+    let [r, g, b] = svg_chart::OTHER_COLOR;
+    egui::Color32::from_rgb(r, g, b)
+}
@@ -0,0 +1,362 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::fmt::Write;
+
+use crate::{format_size, FolderInfo, FsNode};
+
+/// Slice and bar colors, shared with the pie chart in the window.
+pub const CHART_PALETTE: [[u8; 3]; 10] = [
+    [25, 130, 196],
+    [106, 176, 76],
+    [234, 67, 53],
+    [250, 187, 5],
+    [145, 65, 172],
+    [66, 133, 244],
+    [219, 68, 55],
+    [244, 180, 0],
+    [15, 157, 88],
+    [66, 133, 244],
+];
+/// Color of the slice that groups the rows without a color of their own.
+pub const OTHER_COLOR: [u8; 3] = [150, 150, 150];
+// Sunburst segments narrower than this are left out, as in the window
+const MIN_SWEEP: f64 = 0.004;
+const FONT: &str = "font-family=\"Segoe UI, Helvetica, Arial, sans-serif\"";
+
+/// Color of the `index`th row, repeating after the palette runs out.
+pub fn palette_color(index: usize) -> [u8; 3] {
+    CHART_PALETTE[index % CHART_PALETTE.len()]
+}
+
+struct Slice {
+    name: String,
+    size: u64,
+    color: [u8; 3],
+}
+
+// The first `limit` rows, plus one "Other" entry for whatever is left
+fn slices(rows: &[FolderInfo], limit: usize) -> Vec<Slice> {
+    let mut slices: Vec<Slice> = rows
+        .iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, info)| Slice {
+            name: display_name(info),
+            size: info.size,
+            color: palette_color(i),
+        })
+        .collect();
+    let rest: u64 = rows.iter().skip(limit).map(|info| info.size).sum();
+    if rest > 0 {
+        let count = rows.len() - limit;
+        slices.push(Slice {
+            name: format!("Other ({} {})", count, if count == 1 { "item" } else { "items" }),
+            size: rest,
+            color: OTHER_COLOR,
+        });
+    }
+    slices
+}
+
+fn display_name(info: &FolderInfo) -> String {
+    info.path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| info.path.display().to_string())
+}
+
+fn rgb(color: [u8; 3]) -> String {
+    format!("rgb({},{},{})", color[0], color[1], color[2])
+}
+
+fn percent(size: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        size as f64 / total as f64 * 100.0
+    }
+}
+
+/// Escapes text for use in SVG or HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut short: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        short.push('…');
+        short
+    }
+}
+
+fn svg_open(out: &mut String, width: f64, height: f64) {
+    let _ = write!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" {FONT}>\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>",
+        w = width,
+        h = height
+    );
+}
+
+/// Renders the top `limit` rows as a vertical bar chart with a size axis,
+/// a name under every bar and the size above it.
+pub fn bar_chart_svg(rows: &[FolderInfo], limit: usize, width: f64, height: f64) -> String {
+    let total: u64 = rows.iter().map(|info| info.size).sum();
+    let bars: Vec<&FolderInfo> = rows.iter().take(limit).collect();
+    let max = bars.iter().map(|info| info.size).max().unwrap_or(0).max(1);
+
+    let (left, right, top, bottom) = (80.0, 20.0, 30.0, 60.0);
+    let plot_width = (width - left - right).max(1.0);
+    let plot_height = (height - top - bottom).max(1.0);
+    let slot = plot_width / bars.len().max(1) as f64;
+
+    let mut out = String::new();
+    svg_open(&mut out, width, height);
+
+    // Horizontal grid lines with size labels
+    for step in 0..=4 {
+        let value = max as f64 * step as f64 / 4.0;
+        let y = top + plot_height - plot_height * step as f64 / 4.0;
+        let _ = write!(
+            out,
+            "<line x1=\"{left}\" y1=\"{y:.1}\" x2=\"{x2:.1}\" y2=\"{y:.1}\" stroke=\"#ddd\"/>\
+             <text x=\"{lx}\" y=\"{ty:.1}\" font-size=\"11\" text-anchor=\"end\" fill=\"#555\">{label}</text>",
+            x2 = left + plot_width,
+            lx = left - 6.0,
+            ty = y + 4.0,
+            label = escape(&format_size(value as u64))
+        );
+    }
+
+    for (i, info) in bars.iter().enumerate() {
+        let bar_height = plot_height * info.size as f64 / max as f64;
+        let x = left + slot * i as f64 + slot * 0.2;
+        let y = top + plot_height - bar_height;
+        let center = left + slot * (i as f64 + 0.5);
+        let name = display_name(info);
+        let max_chars = ((slot / 7.0) as usize).max(3);
+        let _ = write!(
+            out,
+            "<rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{w:.1}\" height=\"{bar_height:.1}\" fill=\"{color}\">\
+             <title>{title}</title></rect>\
+             <text x=\"{center:.1}\" y=\"{vy:.1}\" font-size=\"10\" text-anchor=\"middle\" fill=\"#333\">{value}</text>\
+             <text x=\"{center:.1}\" y=\"{ny:.1}\" font-size=\"11\" text-anchor=\"middle\" fill=\"#222\">{label}</text>",
+            w = slot * 0.6,
+            color = rgb(palette_color(i)),
+            title = escape(&format!("{}: {} ({:.1}%)", name, format_size(info.size), percent(info.size, total))),
+            vy = y - 4.0,
+            value = escape(&format_size(info.size)),
+            ny = top + plot_height + 16.0,
+            label = escape(&truncate(&name, max_chars))
+        );
+    }
+
+    let _ = write!(
+        out,
+        "<line x1=\"{left}\" y1=\"{top}\" x2=\"{left}\" y2=\"{b:.1}\" stroke=\"#888\"/>\
+         <line x1=\"{left}\" y1=\"{b:.1}\" x2=\"{r:.1}\" y2=\"{b:.1}\" stroke=\"#888\"/></svg>",
+        b = top + plot_height,
+        r = left + plot_width
+    );
+    out
+}
+
+/// Renders the top `limit` rows as a pie chart with a legend on the right.
+/// Everything past the limit is grouped into a grey "Other" slice so the
+/// slices always add up to the whole.
+pub fn pie_chart_svg(rows: &[FolderInfo], limit: usize, width: f64, height: f64) -> String {
+    let total: u64 = rows.iter().map(|info| info.size).sum();
+    let slices = slices(rows, limit);
+
+    let legend_width = (width * 0.45).min(320.0);
+    let radius = ((width - legend_width).min(height) / 2.0 - 10.0).max(10.0);
+    let (cx, cy) = ((width - legend_width) / 2.0, height / 2.0);
+
+    let mut out = String::new();
+    svg_open(&mut out, width, height);
+
+    // Start at twelve o'clock and go clockwise
+    let mut angle = -FRAC_PI_2;
+    for slice in &slices {
+        let sweep = if total > 0 { slice.size as f64 / total as f64 * TAU } else { 0.0 };
+        let title = escape(&format!(
+            "{}: {} ({:.1}%)",
+            slice.name,
+            format_size(slice.size),
+            percent(slice.size, total)
+        ));
+        if sweep >= TAU - 1e-9 {
+            // A single full slice can't be drawn as an arc
+            let _ = write!(
+                out,
+                "<circle cx=\"{cx:.1}\" cy=\"{cy:.1}\" r=\"{radius:.1}\" fill=\"{}\" stroke=\"white\"><title>{title}</title></circle>",
+                rgb(slice.color)
+            );
+        } else if sweep > 0.0 {
+            let (x1, y1) = (cx + radius * angle.cos(), cy + radius * angle.sin());
+            let end = angle + sweep;
+            let (x2, y2) = (cx + radius * end.cos(), cy + radius * end.sin());
+            let large_arc = if sweep > std::f64::consts::PI { 1 } else { 0 };
+            let _ = write!(
+                out,
+                "<path d=\"M{cx:.2},{cy:.2} L{x1:.2},{y1:.2} A{radius:.2},{radius:.2} 0 {large_arc} 1 {x2:.2},{y2:.2} Z\" \
+                 fill=\"{}\" stroke=\"white\" stroke-width=\"1\"><title>{title}</title></path>",
+                rgb(slice.color)
+            );
+        }
+        angle += sweep;
+    }
+
+    write_legend(&mut out, &slices, total, width, height, legend_width);
+    out.push_str("</svg>");
+    out
+}
+
+/// Renders the rows as a sunburst like the window's pie view: the inner
+/// ring is the pie of the top `limit` rows plus "Other", and each of the
+/// `rings - 1` further rings splits a segment into its children. The
+/// legend on the right lists the inner ring.
+pub fn sunburst_svg(rows: &[FolderInfo], limit: usize, rings: usize, width: f64, height: f64) -> String {
+    let total: u64 = rows.iter().map(|info| info.size).sum();
+    let slices = slices(rows, limit);
+    let rings = rings.max(1);
+
+    let legend_width = (width * 0.45).min(320.0);
+    let radius = ((width - legend_width).min(height) / 2.0 - 10.0).max(10.0);
+
+    let mut sunburst = Sunburst {
+        out: String::new(),
+        center: ((width - legend_width) / 2.0, height / 2.0),
+        ring_width: radius / rings as f64,
+        rings,
+        total,
+    };
+    svg_open(&mut sunburst.out, width, height);
+
+    let mut angle = -FRAC_PI_2;
+    for (i, slice) in slices.iter().enumerate() {
+        let sweep = if total > 0 { slice.size as f64 / total as f64 * TAU } else { 0.0 };
+        // Only real rows have contents for the outer rings, "Other" has none
+        let node = rows.get(i).filter(|_| i < limit).map(|info| info.node.as_ref());
+        let mut path = vec![slice.name.clone()];
+        sunburst.segment(slice.color, node, &mut path, slice.size, 0, (angle, sweep));
+        angle += sweep;
+    }
+
+    let mut out = sunburst.out;
+    write_legend(&mut out, &slices, total, width, height, legend_width);
+    out.push_str("</svg>");
+    out
+}
+
+struct Sunburst {
+    out: String,
+    center: (f64, f64),
+    ring_width: f64,
+    rings: usize,
+    total: u64,
+}
+
+impl Sunburst {
+    // Draws one segment spanning `(start, sweep)` and, for real rows, the
+    // segments of its children further out. `path` names it in the tooltip.
+    fn segment(&mut self, color: [u8; 3], node: Option<&FsNode>, path: &mut Vec<String>, size: u64, ring: usize, (start, sweep): (f64, f64)) {
+        if sweep < MIN_SWEEP || ring >= self.rings {
+            return;
+        }
+        let title = escape(&format!("{}: {} ({:.1}%)", path.join("/"), format_size(size), percent(size, self.total)));
+        let d = sector_path(self.center, self.ring_width * ring as f64, self.ring_width * (ring + 1) as f64, start, sweep);
+        let _ = write!(
+            self.out,
+            "<path d=\"{d}\" fill=\"{}\" stroke=\"white\" stroke-width=\"1\"><title>{title}</title></path>",
+            rgb(tint(color, ring))
+        );
+
+        let Some(node) = node.filter(|node| node.size > 0) else {
+            return;
+        };
+        let mut children: Vec<&FsNode> = node.children.iter().map(|child| child.as_ref()).collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.size));
+        let mut angle = start;
+        for child in children {
+            let child_sweep = child.size as f64 / node.size as f64 * sweep;
+            path.push(child.name.clone());
+            self.segment(color, Some(child), path, child.size, ring + 1, (angle, child_sweep));
+            path.pop();
+            angle += child_sweep;
+        }
+    }
+}
+
+fn write_legend(out: &mut String, slices: &[Slice], total: u64, width: f64, height: f64, legend_width: f64) {
+    let legend_x = width - legend_width + 10.0;
+    let line_height = 20.0;
+    let legend_top = (height - line_height * slices.len() as f64).max(0.0) / 2.0;
+    let max_chars = ((legend_width - 40.0) / 6.5) as usize;
+    for (i, slice) in slices.iter().enumerate() {
+        let y = legend_top + line_height * i as f64;
+        let label = format!(
+            "{}: {} ({:.1}%)",
+            slice.name,
+            format_size(slice.size),
+            percent(slice.size, total)
+        );
+        let _ = write!(
+            out,
+            "<rect x=\"{legend_x:.1}\" y=\"{ry:.1}\" width=\"12\" height=\"12\" fill=\"{}\"/>\
+             <text x=\"{tx:.1}\" y=\"{ty:.1}\" font-size=\"12\" fill=\"#222\">{}</text>",
+            rgb(slice.color),
+            escape(&truncate(&label, max_chars.max(10))),
+            ry = y + 4.0,
+            tx = legend_x + 18.0,
+            ty = y + 14.0
+        );
+    }
+}
+
+// Outline of a ring sector between radii `inner` and `outer`. Each arc is
+// drawn in two halves so a full circle works too.
+fn sector_path((cx, cy): (f64, f64), inner: f64, outer: f64, start: f64, sweep: f64) -> String {
+    let at = |angle: f64, r: f64| (cx + r * angle.cos(), cy + r * angle.sin());
+    let (middle, end) = (start + sweep / 2.0, start + sweep);
+    let (ox1, oy1) = at(start, outer);
+    let (ox2, oy2) = at(middle, outer);
+    let (ox3, oy3) = at(end, outer);
+    let mut d = if inner > 0.0 {
+        let (ix3, iy3) = at(end, inner);
+        let (ix2, iy2) = at(middle, inner);
+        let (ix1, iy1) = at(start, inner);
+        format!(
+            "M{ox1:.2},{oy1:.2} A{outer:.2},{outer:.2} 0 0 1 {ox2:.2},{oy2:.2} A{outer:.2},{outer:.2} 0 0 1 {ox3:.2},{oy3:.2} \
+             L{ix3:.2},{iy3:.2} A{inner:.2},{inner:.2} 0 0 0 {ix2:.2},{iy2:.2} A{inner:.2},{inner:.2} 0 0 0 {ix1:.2},{iy1:.2}"
+        )
+    } else {
+        format!(
+            "M{cx:.2},{cy:.2} L{ox1:.2},{oy1:.2} A{outer:.2},{outer:.2} 0 0 1 {ox2:.2},{oy2:.2} \
+             A{outer:.2},{outer:.2} 0 0 1 {ox3:.2},{oy3:.2}"
+        )
+    };
+    d.push_str(" Z");
+    d
+}
+
+/// Lightens `color` for sunburst ring `ring`, so each ring further out is
+/// a bit lighter than the one inside it.
+pub fn tint(color: [u8; 3], ring: usize) -> [u8; 3] {
+    let factor = 0.18 * ring.min(4) as f64;
+    color.map(|c| (c as f64 + (255.0 - c as f64) * factor) as u8)
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::archive::{self, ArchiveFormat};
+use crate::scanner::{self, EventSink, ScanEvent};
+
+/// A file or directory captured during a scan.
+///
+/// Directories carry the sum of their children's sizes in `size`, so the
+/// top-level numbers shown in the UI are just the root nodes' sizes.
+/// `allocated` is the space actually reserved on disk, which differs from
+/// `size` for sparse, compressed or preallocated files.
+///
+/// Archives that were looked into are files with children: the entries
+/// inside are virtual nodes that only exist in the tree. `uncompressed` is
+/// set on the archive and on every entry below it.
+///
+/// For directories `modified` is the newest modification time found
+/// anywhere below them, which tells when the folder was last touched.
+#[derive(Debug, Clone, Default)]
+pub struct FsNode {
+    /// File or folder name, without the path leading to it.
+    pub name: String,
+    /// Bytes of file content, summed over everything below a directory.
+    pub size: u64,
+    /// Bytes taken on disk, which differs from `size` for sparse,
+    /// compressed and preallocated files.
+    pub allocated: u64,
+    /// A directory, as opposed to a file or an archive.
+    pub is_dir: bool,
+    /// Last modification time, if the filesystem reports one.
+    pub modified: Option<SystemTime>,
+    /// For archives and their entries, the size before compression.
+    pub uncompressed: Option<u64>,
+    /// Entries of a directory or opened archive, in no particular order.
+    pub children: Vec<Arc<FsNode>>,
+}
+
+/// What a scan should do beyond adding up sizes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// List the entries of zip and tar archives as virtual sub-folders.
+    pub look_inside_archives: bool,
+}
+
+/// Everything about a finished scan apart from the tree itself.
+#[derive(Debug, Clone)]
+pub struct ScanInfo {
+    /// The directory whose folders were scanned.
+    pub root: PathBuf,
+    /// When the scan ran. Missing for results imported from a file that
+    /// doesn't record it.
+    pub started: Option<SystemTime>,
+    /// When the scan ended, missing like `started`.
+    pub finished: Option<SystemTime>,
+    /// The options the scan ran with.
+    pub options: ScanOptions,
+    /// Folders that were left out because they could not be read.
+    pub errors: Vec<ScanError>,
+}
+
+/// A folder that was left out of the results because it could not be read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanError {
+    /// The folder that was left out.
+    pub path: PathBuf,
+    /// Why reading it failed, as reported by the operating system.
+    pub message: String,
+}
+
+impl FsNode {
+    /// Whether the node has anything to drill into.
+    pub fn is_browsable(&self) -> bool {
+        self.is_dir || !self.children.is_empty()
+    }
+
+    /// Number of files below this node, or 1 for a file. An archive counts
+    /// as a single file.
+    pub fn file_count(&self) -> u64 {
+        if self.is_dir {
+            self.children.iter().map(|child| child.file_count()).sum()
+        } else {
+            1
+        }
+    }
+
+    /// Calls `f` with the full path of every file below this node.
+    /// `parent` is the directory that contains this node. Entries inside
+    /// archives are not visited since they don't exist on disk.
+    pub fn visit_files(&self, parent: &Path, f: &mut impl FnMut(PathBuf, &FsNode)) {
+        let path = parent.join(&self.name);
+        if self.is_dir {
+            for child in &self.children {
+                child.visit_files(&path, f);
+            }
+        } else {
+            f(path, self);
+        }
+    }
+
+    /// Removes the entry at `path`, given as names relative to this node,
+    /// and takes its sizes off every directory on the way. Shared nodes
+    /// along the path are copied first. Returns the removed node.
+    pub fn remove_descendant(&mut self, path: &[&str]) -> Option<Arc<FsNode>> {
+        let (first, rest) = path.split_first()?;
+        let index = self.children.iter().position(|child| child.name == *first)?;
+        let removed = if rest.is_empty() {
+            self.children.remove(index)
+        } else {
+            Arc::make_mut(&mut self.children[index]).remove_descendant(rest)?
+        };
+        self.size = self.size.saturating_sub(removed.size);
+        self.allocated = self.allocated.saturating_sub(removed.allocated);
+        Some(removed)
+    }
+}
+
+/// Recursively reads `path` into an [`FsNode`] tree.
+pub(crate) fn scan_dir_tree(path: &Path, options: &ScanOptions, sink: &EventSink) -> Result<FsNode, std::io::Error> {
+    if sink.is_cancelled() {
+        return Err(scanner::cancelled());
+    }
+    sink.send(ScanEvent::DirectoryEntered(path.to_path_buf()));
+
+    let mut node = FsNode {
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string()),
+        is_dir: true,
+        modified: fs::metadata(path).and_then(|m| m.modified()).ok(),
+        ..Default::default()
+    };
+    let entries = fs::read_dir(path)?;
+
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let child = if path.is_dir() {
+            scan_dir_tree(&path, options, sink)?
+        } else {
+            let metadata = entry.metadata()?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let mut file = FsNode {
+                size: metadata.len(),
+                allocated: allocated_size(&path, &metadata),
+                modified: metadata.modified().ok(),
+                ..Default::default()
+            };
+            if options.look_inside_archives {
+                if let Some(format) = ArchiveFormat::from_name(&name) {
+                    // An unreadable archive is still counted as a plain file
+                    if let Ok(entries) = archive::read_archive(&path, format, file.size) {
+                        file.uncompressed = Some(entries.iter().map(|e| e.uncompressed.unwrap_or(0)).sum());
+                        file.children = entries;
+                    }
+                }
+            }
+            file.name = name;
+            file
+        };
+        node.size += child.size;
+        node.allocated += child.allocated;
+        node.modified = node.modified.max(child.modified);
+        node.children.push(Arc::new(child));
+    }
+    Ok(node)
+}
+
+#[cfg(unix)]
+fn allocated_size(_path: &Path, metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    // st_blocks is always in 512-byte units, regardless of the block size
+    metadata.blocks() * 512
+}
+
+#[cfg(windows)]
+fn allocated_size(path: &Path, metadata: &fs::Metadata) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::{GetCompressedFileSizeW, INVALID_FILE_SIZE};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high = 0;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    // INVALID_FILE_SIZE is also a valid low word, so check the error code too
+    if low == INVALID_FILE_SIZE && std::io::Error::last_os_error().raw_os_error() != Some(0) {
+        return metadata.len();
+    }
+    ((high as u64) << 32) | low as u64
+}
+
+#[cfg(not(any(unix, windows)))]
+fn allocated_size(_path: &Path, metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
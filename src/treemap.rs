@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use eframe::egui;
+use folder_size_analyzer::svg_chart::palette_color;
+use folder_size_analyzer::{format_size, FolderInfo, FsNode};
+
+// Directories are only subdivided while they are at least this big
+const MIN_NESTED_SIZE: f32 = 24.0;
+const MAX_DEPTH: usize = 4;
+const HEADER_HEIGHT: f32 = 14.0;
+const PADDING: f32 = 2.0;
+
+/// What the treemap tiles are colored by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreemapColor {
+    TopFolder,
+    Extension,
+}
+
+struct Tile {
+    rect: egui::Rect,
+    // From the top-level entry of the view down to the tile's own node
+    chain: Vec<Arc<FsNode>>,
+}
+
+/// Draws the rows as a nested, squarified treemap filling `size`.
+///
+/// Returns the chain of nodes below the view down to the clicked tile,
+/// outermost first, so the caller can zoom into it.
+pub fn show(
+    ui: &mut egui::Ui,
+    rows: &[FolderInfo],
+    size: egui::Vec2,
+    color_by: TreemapColor,
+) -> Option<Vec<Arc<FsNode>>> {
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::click());
+    let total: u64 = rows.iter().map(|info| info.size).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let nodes: Vec<Arc<FsNode>> = rows.iter().map(|info| info.node.clone()).collect();
+    let mut tiles = Vec::new();
+    for (index, (node, rect)) in layout(&nodes, response.rect).into_iter().enumerate() {
+        let base = palette_color(index);
+        draw_tile(&painter, &mut tiles, vec![node], rect, base, color_by, 0);
+    }
+
+    // Tiles are pushed parent first, so the last hit is the innermost one
+    let pointer = response.hover_pos()?;
+    let hovered = tiles.iter().rev().find(|tile| tile.rect.contains(pointer))?;
+    painter.rect_stroke(hovered.rect, 0.0, egui::Stroke::new(2.0, egui::Color32::WHITE));
+
+    let node = hovered.chain.last()?;
+    let path: Vec<&str> = hovered.chain.iter().map(|node| node.name.as_str()).collect();
+    let response = response.on_hover_ui_at_pointer(|ui| {
+        ui.strong(path.join("/"));
+        ui.label(format!(
+            "{} ({:.1}% of view)",
+            format_size(node.size),
+            node.size as f64 / total as f64 * 100.0
+        ));
+        if node.is_browsable() {
+            ui.label(format!("{} files", node.file_count()));
+        }
+    });
+    response.clicked().then(|| hovered.chain.clone())
+}
+
+fn draw_tile(
+    painter: &egui::Painter,
+    tiles: &mut Vec<Tile>,
+    chain: Vec<Arc<FsNode>>,
+    rect: egui::Rect,
+    base: [u8; 3],
+    color_by: TreemapColor,
+    depth: usize,
+) {
+    if rect.width() < 1.0 || rect.height() < 1.0 {
+        return;
+    }
+    let node = chain.last().unwrap().clone();
+    let color = match color_by {
+        TreemapColor::TopFolder => shade(base, depth),
+        TreemapColor::Extension if node.is_browsable() => shade([110, 110, 110], depth),
+        TreemapColor::Extension => extension_color(&node.name),
+    };
+    painter.rect_filled(rect, 0.0, color);
+    painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, egui::Color32::from_black_alpha(120)));
+
+    let nested = node.is_browsable()
+        && depth + 1 < MAX_DEPTH
+        && rect.width() >= MIN_NESTED_SIZE
+        && rect.height() >= MIN_NESTED_SIZE + HEADER_HEIGHT;
+    if rect.width() > 30.0 && rect.height() > HEADER_HEIGHT {
+        painter.with_clip_rect(rect.shrink(1.0)).text(
+            rect.min + egui::vec2(3.0, 1.0),
+            egui::Align2::LEFT_TOP,
+            &node.name,
+            egui::FontId::proportional(11.0),
+            egui::Color32::WHITE,
+        );
+    }
+    tiles.push(Tile {
+        rect,
+        chain: chain.clone(),
+    });
+
+    if nested {
+        let inner = egui::Rect::from_min_max(
+            rect.min + egui::vec2(PADDING, HEADER_HEIGHT),
+            rect.max - egui::vec2(PADDING, PADDING),
+        );
+        let mut children: Vec<Arc<FsNode>> = node.children.iter().filter(|c| c.size > 0).cloned().collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.size));
+        for (child, child_rect) in layout(&children, inner) {
+            let mut child_chain = chain.clone();
+            child_chain.push(child);
+            draw_tile(painter, tiles, child_chain, child_rect, base, color_by, depth + 1);
+        }
+    }
+}
+
+fn layout(nodes: &[Arc<FsNode>], rect: egui::Rect) -> Vec<(Arc<FsNode>, egui::Rect)> {
+    let sizes: Vec<f32> = nodes.iter().map(|node| node.size as f32).collect();
+    nodes.iter().cloned().zip(squarify(&sizes, rect)).collect()
+}
+
+/// Splits `rect` into one rectangle per size, with areas proportional to
+/// the sizes and aspect ratios kept close to square. `sizes` should be
+/// sorted largest first.
+pub fn squarify(sizes: &[f32], rect: egui::Rect) -> Vec<egui::Rect> {
+    let total: f32 = sizes.iter().sum();
+    if total <= 0.0 {
+        return vec![egui::Rect::from_min_size(rect.min, egui::Vec2::ZERO); sizes.len()];
+    }
+    let scale = rect.area() / total;
+    let areas: Vec<f32> = sizes.iter().map(|size| size * scale).collect();
+
+    let mut out = Vec::with_capacity(areas.len());
+    let mut remaining = rect;
+    let mut start = 0;
+    while start < areas.len() {
+        // Rows are laid along the shorter side of what is left
+        let side = remaining.width().min(remaining.height()).max(f32::EPSILON);
+        let mut end = start + 1;
+        let mut row_area = areas[start];
+        let mut worst = worst_ratio(&areas[start..end], row_area, side);
+        while end < areas.len() {
+            let candidate = worst_ratio(&areas[start..=end], row_area + areas[end], side);
+            if candidate > worst {
+                break;
+            }
+            row_area += areas[end];
+            worst = candidate;
+            end += 1;
+        }
+
+        if remaining.width() >= remaining.height() {
+            let thickness = if remaining.height() > 0.0 { row_area / remaining.height() } else { 0.0 };
+            let mut y = remaining.top();
+            for area in &areas[start..end] {
+                let length = if thickness > 0.0 { area / thickness } else { 0.0 };
+                out.push(egui::Rect::from_min_size(egui::pos2(remaining.left(), y), egui::vec2(thickness, length)));
+                y += length;
+            }
+            remaining.min.x += thickness;
+        } else {
+            let thickness = if remaining.width() > 0.0 { row_area / remaining.width() } else { 0.0 };
+            let mut x = remaining.left();
+            for area in &areas[start..end] {
+                let length = if thickness > 0.0 { area / thickness } else { 0.0 };
+                out.push(egui::Rect::from_min_size(egui::pos2(x, remaining.top()), egui::vec2(length, thickness)));
+                x += length;
+            }
+            remaining.min.y += thickness;
+        }
+        start = end;
+    }
+    out
+}
+
+// The worst aspect ratio in a row of `areas` laid along a side of `side`
+fn worst_ratio(areas: &[f32], row_area: f32, side: f32) -> f32 {
+    let max = areas.iter().cloned().fold(0.0, f32::max);
+    let min = areas.iter().cloned().fold(f32::INFINITY, f32::min);
+    if min <= 0.0 || row_area <= 0.0 {
+        return f32::INFINITY;
+    }
+    let side2 = side * side;
+    let row2 = row_area * row_area;
+    (side2 * max / row2).max(row2 / (side2 * min))
+}
+
+/// Darkens `color` a little for every level of `depth`, so nesting stays
+/// visible.
+pub fn shade([r, g, b]: [u8; 3], depth: usize) -> egui::Color32 {
+    let factor = 1.0 - 0.12 * depth.min(5) as f32;
+    egui::Color32::from_rgb(
+        (r as f32 * factor) as u8,
+        (g as f32 * factor) as u8,
+        (b as f32 * factor) as u8,
+    )
+}
+
+fn extension_color(name: &str) -> egui::Color32 {
+    let extension = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => extension.to_lowercase(),
+        _ => return egui::Color32::from_rgb(90, 90, 90),
+    };
+    // Stable hash so an extension keeps its color between frames and scans
+    let hash = extension
+        .bytes()
+        .fold(0usize, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as usize));
+    let [r, g, b] = palette_color(hash);
+    egui::Color32::from_rgb(r, g, b)
+}
@@ -0,0 +1,463 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::Duration;
+
+use folder_size_analyzer::{format_size, FolderInfo, FsNode, ScanEvent, ScanOptions, Scanner};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cli::{EXIT_FAILURE, EXIT_OK, EXIT_USAGE};
+
+const USAGE: &str = "\
+Usage: folder-size-analyzer tui [path] [--archives]
+
+Scans <path> (the current directory by default) and browses the result in
+the terminal.
+
+Keys:
+  Up/Down, PgUp/PgDn, Home/End  move
+  Enter, Right                  open folder
+  Backspace, Left               go up
+  u                             switch size units
+  d, Delete                     delete the selected entry (asks first)
+  q, Esc                        quit";
+
+const BAR_WIDTH: usize = 12;
+// How often the screen is redrawn while waiting for the scan
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Units {
+    Decimal,
+    Binary,
+    Bytes,
+}
+
+impl Units {
+    fn next(self) -> Self {
+        match self {
+            Units::Decimal => Units::Binary,
+            Units::Binary => Units::Bytes,
+            Units::Bytes => Units::Decimal,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Units::Decimal => "KB/MB/GB",
+            Units::Binary => "KiB/MiB/GiB",
+            Units::Bytes => "bytes",
+        }
+    }
+
+    fn format(self, bytes: u64) -> String {
+        match self {
+            Units::Decimal => format_size(bytes),
+            Units::Bytes => bytes.to_string(),
+            Units::Binary => {
+                const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+                let mut size = bytes as f64;
+                let mut unit = 0;
+                while size >= 1024.0 && unit < UNITS.len() - 1 {
+                    size /= 1024.0;
+                    unit += 1;
+                }
+                if unit == 0 {
+                    format!("{} B", bytes)
+                } else {
+                    format!("{:.2} {}", size, UNITS[unit])
+                }
+            }
+        }
+    }
+}
+
+struct Browser {
+    root: PathBuf,
+    folders: Vec<FolderInfo>,
+    // Names of the entries opened below the root, outermost first
+    path: Vec<String>,
+    // Selection of every parent level, restored when going back up
+    parent_selection: Vec<usize>,
+    list: ListState,
+    page_height: usize,
+    units: Units,
+    confirm_delete: bool,
+    status: Option<String>,
+}
+
+/// Runs the terminal front end and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let mut path = None;
+    let mut options = ScanOptions::default();
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return EXIT_OK;
+            }
+            "--archives" => options.look_inside_archives = true,
+            other if other.starts_with('-') => {
+                eprintln!("error: unknown option '{}'\n\n{}", other, USAGE);
+                return EXIT_USAGE;
+            }
+            other => {
+                if path.replace(PathBuf::from(other)).is_some() {
+                    eprintln!("error: only one path can be scanned at a time\n\n{}", USAGE);
+                    return EXIT_USAGE;
+                }
+            }
+        }
+    }
+    let root = match path.map(Ok).unwrap_or_else(std::env::current_dir) {
+        Ok(root) if root.is_dir() => root,
+        Ok(root) => {
+            eprintln!("error: not a directory: {}", root.display());
+            return EXIT_FAILURE;
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let mut terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            ratatui::restore();
+            eprintln!("error: could not start the terminal UI: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+    let result = run_app(&mut terminal, root, options);
+    ratatui::restore();
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn run_app(terminal: &mut DefaultTerminal, root: PathBuf, options: ScanOptions) -> Result<(), String> {
+    let (sender, events) = mpsc::channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let scanner = Scanner::new(&root).options(options).cancel_flag(cancel.clone());
+    rayon::spawn(move || scanner.scan_with_events(sender));
+
+    let report = match wait_for_scan(terminal, &events, &cancel).map_err(|e| e.to_string())? {
+        ScanEvent::Finished(report) => report,
+        ScanEvent::Failed(error) => {
+            return Err(format!("could not read {}: {}", error.path.display(), error.message));
+        }
+        _ => return Ok(()),
+    };
+
+    let mut browser = Browser {
+        root,
+        folders: report.folders,
+        path: Vec::new(),
+        parent_selection: Vec::new(),
+        list: ListState::default().with_selected(Some(0)),
+        page_height: 1,
+        units: Units::Decimal,
+        confirm_delete: false,
+        status: (!report.info.errors.is_empty())
+            .then(|| format!("{} folders could not be read", report.info.errors.len())),
+    };
+    loop {
+        terminal.draw(|frame| browser.draw(frame)).map_err(|e| e.to_string())?;
+        if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+            if key.kind == KeyEventKind::Press && !browser.handle_key(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Shows the progress until the scan sends its last event, which is returned
+fn wait_for_scan(
+    terminal: &mut DefaultTerminal,
+    events: &Receiver<ScanEvent>,
+    cancel: &AtomicBool,
+) -> io::Result<ScanEvent> {
+    let (mut done, mut total, mut current_path) = (0, 0, String::new());
+    loop {
+        for event in events.try_iter() {
+            match event {
+                ScanEvent::Started { folders, .. } => total = folders,
+                ScanEvent::DirectoryEntered(path) => current_path = path.display().to_string(),
+                ScanEvent::FolderCompleted(_) | ScanEvent::Error(_) => done += 1,
+                last => return Ok(last),
+            }
+        }
+
+        terminal.draw(|frame| {
+            let [title, gauge, path] = Layout::vertical([Constraint::Length(1); 3])
+                .margin(1)
+                .areas(frame.area());
+            frame.render_widget(Line::from("Scanning… (q to cancel)").bold(), title);
+            let ratio = if total > 0 { done as f64 / total as f64 } else { 0.0 };
+            frame.render_widget(
+                Gauge::default()
+                    .ratio(ratio.min(1.0))
+                    .label(format!("{}/{} folders", done, total)),
+                gauge,
+            );
+            frame.render_widget(Line::from(current_path.as_str()).dim(), path);
+        })?;
+
+        if event::poll(TICK)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+impl Browser {
+    // None at the root, where the scanned folders are listed
+    fn current_node(&self) -> Option<&FsNode> {
+        let (first, rest) = self.path.split_first()?;
+        let mut node = self.folders.iter().find(|folder| folder.node.name == *first)?.node.as_ref();
+        for name in rest {
+            node = node.children.iter().find(|child| child.name == *name)?;
+        }
+        Some(node)
+    }
+
+    fn entries(&self) -> Vec<Arc<FsNode>> {
+        let mut entries: Vec<Arc<FsNode>> = match self.current_node() {
+            Some(node) => node.children.clone(),
+            None => self.folders.iter().map(|folder| folder.node.clone()).collect(),
+        };
+        entries.sort_by_key(|entry| Reverse(entry.size));
+        entries
+    }
+
+    fn current_size(&self) -> u64 {
+        match self.current_node() {
+            Some(node) => node.size,
+            None => self.folders.iter().map(|folder| folder.size).sum(),
+        }
+    }
+
+    // Archive entries only exist in the tree, so they can't be deleted
+    fn in_archive(&self) -> bool {
+        let Some((first, rest)) = self.path.split_first() else {
+            return false;
+        };
+        let Some(folder) = self.folders.iter().find(|folder| folder.node.name == *first) else {
+            return false;
+        };
+        let mut node = folder.node.as_ref();
+        for name in rest {
+            if !node.is_dir {
+                return true;
+            }
+            match node.children.iter().find(|child| child.name == *name) {
+                Some(child) => node = child,
+                None => return false,
+            }
+        }
+        !node.is_dir
+    }
+
+    fn disk_path(&self, name: &str) -> PathBuf {
+        let mut path = self.root.clone();
+        path.extend(&self.path);
+        path.join(name)
+    }
+
+    fn selected_entry(&self) -> Option<Arc<FsNode>> {
+        self.entries().get(self.list.selected()?).cloned()
+    }
+
+    // Returns false when the browser should close
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        if self.confirm_delete {
+            self.confirm_delete = false;
+            if matches!(key, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete_selected();
+            }
+            return true;
+        }
+
+        self.status = None;
+        let count = self.entries().len();
+        let selected = self.list.selected().unwrap_or(0);
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.list.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down | KeyCode::Char('j') => self.select_clamped(selected + 1, count),
+            KeyCode::PageUp => self.list.select(Some(selected.saturating_sub(self.page_height))),
+            KeyCode::PageDown => self.select_clamped(selected + self.page_height, count),
+            KeyCode::Home | KeyCode::Char('g') => self.list.select(Some(0)),
+            KeyCode::End | KeyCode::Char('G') => self.select_clamped(usize::MAX, count),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(entry) = self.selected_entry().filter(|entry| entry.is_browsable()) {
+                    self.path.push(entry.name.clone());
+                    self.parent_selection.push(selected);
+                    self.list.select(Some(0));
+                }
+            }
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => self.go_up(),
+            KeyCode::Char('u') => self.units = self.units.next(),
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if self.in_archive() {
+                    self.status = Some("Entries inside archives can't be deleted".to_string());
+                } else if self.selected_entry().is_some() {
+                    self.confirm_delete = true;
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn go_up(&mut self) {
+        if self.path.pop().is_some() {
+            let parent = self.parent_selection.pop().unwrap_or(0);
+            self.list.select(Some(parent));
+        }
+    }
+
+    fn select_clamped(&mut self, index: usize, count: usize) {
+        self.list.select(Some(index.min(count.saturating_sub(1))));
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let path = self.disk_path(&entry.name);
+        let removed = if entry.is_dir {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        if let Err(e) = removed {
+            self.status = Some(format!("Could not delete {}: {}", path.display(), e));
+            return;
+        }
+
+        match self.path.split_first() {
+            None => self.folders.retain(|folder| folder.node.name != entry.name),
+            Some((first, rest)) => {
+                if let Some(folder) = self.folders.iter_mut().find(|folder| folder.node.name == *first) {
+                    let mut names: Vec<&str> = rest.iter().map(String::as_str).collect();
+                    names.push(&entry.name);
+                    Arc::make_mut(&mut folder.node).remove_descendant(&names);
+                    folder.size = folder.node.size;
+                }
+                self.folders.sort_by_key(|folder| Reverse(folder.size));
+            }
+        }
+        let selected = self.list.selected().unwrap_or(0);
+        self.select_clamped(selected, self.entries().len());
+        self.status = Some(format!("Deleted {} ({})", path.display(), self.units.format(entry.size)));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let entries = self.entries();
+        let total = self.current_size();
+        let mut location = self.root.clone();
+        location.extend(&self.path);
+        frame.render_widget(
+            Line::from(format!(
+                " {}  —  {} in {} items",
+                location.display(),
+                self.units.format(total),
+                entries.len()
+            ))
+            .bold()
+            .reversed(),
+            header,
+        );
+
+        // Bars are relative to the largest entry, percentages to the folder
+        let largest = entries.first().map(|entry| entry.size).unwrap_or(0).max(1);
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|entry| {
+                let filled = (entry.size as f64 / largest as f64 * BAR_WIDTH as f64).round() as usize;
+                let percent = if total > 0 {
+                    entry.size as f64 / total as f64 * 100.0
+                } else {
+                    0.0
+                };
+                ListItem::new(format!(
+                    "{:>12} {:>5.1}% [{:<width$}] {}{}",
+                    self.units.format(entry.size),
+                    percent,
+                    "#".repeat(filled.min(BAR_WIDTH)),
+                    entry.name,
+                    if entry.is_browsable() { "/" } else { "" },
+                    width = BAR_WIDTH
+                ))
+            })
+            .collect();
+        self.page_height = body.height.max(1) as usize;
+        let list = List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, body, &mut self.list);
+
+        let help = format!(
+            " ↑↓ move  Enter open  Backspace up  u units ({})  d delete  q quit",
+            self.units.label()
+        );
+        let status = self.status.clone().unwrap_or(help);
+        frame.render_widget(Line::from(status).reversed(), footer);
+
+        if self.confirm_delete {
+            if let Some(entry) = self.selected_entry() {
+                self.draw_confirm(frame, &entry);
+            }
+        }
+    }
+
+    fn draw_confirm(&self, frame: &mut Frame, entry: &FsNode) {
+        let area = centered(frame.area(), 64, 7);
+        let text = format!(
+            "Delete {}?\n{}, this cannot be undone.\n\ny: delete   any other key: cancel",
+            self.disk_path(&entry.name).display(),
+            self.units.format(entry.size)
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" Delete ")),
+            area,
+        );
+    }
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}